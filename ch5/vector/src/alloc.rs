@@ -0,0 +1,140 @@
+use std::alloc::{Layout, LayoutError};
+use std::ptr::NonNull;
+
+/// The allocator failed to provide the memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl std::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// Error returned by the fallible allocation API of the `Vector`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryReserveError {
+    /// Requested capacity exceeds `isize::MAX` bytes or overflows `usize`
+    CapacityOverflow,
+    /// The allocator returned an error for the given layout
+    AllocError { layout: Layout },
+}
+
+impl TryReserveError {
+    /// Reports the error the way infallible operations do - panicking on overflow and aborting on
+    /// allocation failure
+    pub(crate) fn handle(self) -> ! {
+        match self {
+            Self::CapacityOverflow => panic!("capacity overflow"),
+            Self::AllocError { layout } => std::alloc::handle_alloc_error(layout),
+        }
+    }
+}
+
+impl From<LayoutError> for TryReserveError {
+    fn from(_: LayoutError) -> Self {
+        Self::CapacityOverflow
+    }
+}
+
+impl std::fmt::Display for TryReserveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CapacityOverflow => f.write_str("capacity overflow"),
+            Self::AllocError { layout } => {
+                write!(f, "memory allocation of {} bytes failed", layout.size())
+            }
+        }
+    }
+}
+
+impl std::error::Error for TryReserveError {}
+
+/// Memory allocator used by the `Vector`.
+///
+/// It is a simplified stable version of the unstable `std::alloc::Allocator` trait.
+///
+/// # Safety
+/// * The memory returned from `allocate` and `reallocate` has to stay valid until it is passed to
+///   `deallocate` or `reallocate`, or the allocator is dropped.
+/// * The allocator is never called with zero-sized layouts.
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    /// `ptr` has to be allocated by this allocator with the `layout`
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Moves the memory block to the one fitting `newlayout`, preserving its content up to the
+    /// smaller of the sizes. On error the old memory block stays untouched.
+    ///
+    /// # Safety
+    /// `ptr` has to be allocated by this allocator with the `layout`, and `newlayout` has to have
+    /// the same alignment as the `layout`
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        newlayout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let newptr = self.allocate(newlayout)?;
+        std::ptr::copy_nonoverlapping(
+            ptr.as_ptr(),
+            newptr.as_ptr(),
+            layout.size().min(newlayout.size()),
+        );
+        self.deallocate(ptr, layout);
+        Ok(newptr)
+    }
+}
+
+unsafe impl<A> Allocator for &A
+where
+    A: Allocator + ?Sized,
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        newlayout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        (**self).reallocate(ptr, layout, newlayout)
+    }
+}
+
+/// The global memory allocator - the one used by `Box` and `std::Vec`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Global;
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        // Safety:
+        // The layout is never zero-sized by the `Allocator` contract
+        let ptr = unsafe { std::alloc::alloc(layout) };
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        std::alloc::dealloc(ptr.as_ptr(), layout)
+    }
+
+    unsafe fn reallocate(
+        &self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+        newlayout: Layout,
+    ) -> Result<NonNull<u8>, AllocError> {
+        let ptr = std::alloc::realloc(ptr.as_ptr(), layout, newlayout.size());
+        NonNull::new(ptr).ok_or(AllocError)
+    }
+}
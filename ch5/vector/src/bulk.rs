@@ -0,0 +1,532 @@
+use std::ops::{Range, RangeBounds};
+use std::ptr::drop_in_place;
+
+use crate::drain::range;
+use crate::iterator::IntoIter;
+use crate::{Allocator, Vector};
+
+/// Compacts the buffer while its items are being filtered out.
+///
+/// Items in `ptr..ptr+write` are kept, and items in `ptr+write..ptr+read` are already dropped or
+/// moved. On drop (also when unwinding from the panicking predicate) the unprocessed
+/// `ptr+read..ptr+original` items are moved to close the gap, and the length is restored. Until
+/// then the vector length is zero, so nothing is ever dropped twice.
+pub(crate) struct Compact<'a, T> {
+    pub(crate) ptr: *mut T,
+    len: &'a mut usize,
+    pub(crate) read: usize,
+    write: usize,
+    pub(crate) original: usize,
+}
+
+impl<'a, T> Compact<'a, T> {
+    pub(crate) fn new(ptr: *mut T, len: &'a mut usize) -> Self {
+        unsafe { Self::starting_at(ptr, len, 0) }
+    }
+
+    /// Compacts the buffer keeping the first `start` items untouched
+    ///
+    /// Safety:
+    /// `start <= *len`
+    pub(crate) unsafe fn starting_at(ptr: *mut T, len: &'a mut usize, start: usize) -> Self {
+        let original = std::mem::take(len);
+
+        Self {
+            ptr,
+            len,
+            read: start,
+            write: start,
+            original,
+        }
+    }
+
+    /// Keeps the item at `read`, moving it to `write`
+    ///
+    /// Safety:
+    /// `read < original`
+    pub(crate) unsafe fn keep(&mut self) {
+        if self.read != self.write {
+            std::ptr::copy_nonoverlapping(self.ptr.add(self.read), self.ptr.add(self.write), 1);
+        }
+        self.read += 1;
+        self.write += 1;
+    }
+
+    /// Drops the item at `read`
+    ///
+    /// Safety:
+    /// `read < original`
+    unsafe fn delete(&mut self) {
+        let item = self.ptr.add(self.read);
+        self.read += 1;
+        drop_in_place(item);
+    }
+
+    /// Moves out the item at `read`
+    ///
+    /// Safety:
+    /// `read < original`
+    pub(crate) unsafe fn take(&mut self) -> T {
+        let item = self.ptr.add(self.read).read();
+        self.read += 1;
+        item
+    }
+}
+
+impl<T> Drop for Compact<'_, T> {
+    fn drop(&mut self) {
+        let unprocessed = self.original - self.read;
+        unsafe {
+            std::ptr::copy(
+                self.ptr.add(self.read),
+                self.ptr.add(self.write),
+                unprocessed,
+            )
+        };
+        *self.len = self.write + unprocessed;
+    }
+}
+
+impl<T, A: Allocator> Vector<T, A> {
+    /// Shortens the vector to `len` elements, dropping the rest. Does nothing if the vector is
+    /// already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let tail = std::ptr::slice_from_raw_parts_mut(
+            unsafe { self.ptr.as_ptr().add(len) },
+            self.len - len,
+        );
+        // Length is updated first, so the panicking drop leaks instead of double dropping
+        self.len = len;
+        unsafe { drop_in_place(tail) };
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Resizes the vector to `new_len`, filling new space with results of `f`
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - self.len);
+        while self.len < new_len {
+            unsafe { self.ptr.as_ptr().add(self.len).write(f()) };
+            self.len += 1;
+        }
+    }
+
+    /// Resizes the vector to `new_len`, filling new space with clones of `value`
+    pub fn resize(&mut self, new_len: usize, value: T)
+    where
+        T: Clone,
+    {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.reserve(new_len - self.len);
+        while self.len + 1 < new_len {
+            unsafe { self.ptr.as_ptr().add(self.len).write(value.clone()) };
+            self.len += 1;
+        }
+
+        // The last one does not need cloning
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+    }
+
+    /// Clones and appends all the elements of the `other`
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        self.reserve(other.len());
+        for item in other {
+            unsafe { self.ptr.as_ptr().add(self.len).write(item.clone()) };
+            self.len += 1;
+        }
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, preserving their order.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.retain_mut(|item| f(item))
+    }
+
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let mut compact = Compact::new(self.ptr.as_ptr(), &mut self.len);
+
+        while compact.read < compact.original {
+            let item = unsafe { &mut *compact.ptr.add(compact.read) };
+            if f(item) {
+                unsafe { compact.keep() };
+            } else {
+                unsafe { compact.delete() };
+            }
+        }
+    }
+
+    /// Removes consecutive elements for which `same_bucket(item, previous)` returns `true`,
+    /// keeping the first one of every run.
+    pub fn dedup_by(&mut self, mut same_bucket: impl FnMut(&mut T, &mut T) -> bool) {
+        if self.len <= 1 {
+            return;
+        }
+
+        let mut compact = Compact::new(self.ptr.as_ptr(), &mut self.len);
+        // The first item is always kept
+        unsafe { compact.keep() };
+
+        while compact.read < compact.original {
+            let item = unsafe { &mut *compact.ptr.add(compact.read) };
+            let prev = unsafe { &mut *compact.ptr.add(compact.write - 1) };
+            if same_bucket(item, prev) {
+                unsafe { compact.delete() };
+            } else {
+                unsafe { compact.keep() };
+            }
+        }
+    }
+
+    pub fn dedup_by_key<K: PartialEq>(&mut self, mut key: impl FnMut(&mut T) -> K) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
+    /// Splits the vector in two at `at`, returning the `at..` elements
+    pub fn split_off(&mut self, at: usize) -> Self
+    where
+        A: Clone,
+    {
+        if at > self.len {
+            panic!("Index out of bounds");
+        }
+
+        let tail_len = self.len - at;
+        let mut other = Self::with_capacity_in(tail_len, self.alloc.clone());
+        unsafe {
+            std::ptr::copy_nonoverlapping(self.ptr.as_ptr().add(at), other.ptr.as_ptr(), tail_len)
+        };
+        self.len = at;
+        other.len = tail_len;
+
+        other
+    }
+
+    /// Moves all the elements of `other` to the end of `self`, leaving `other` empty
+    pub fn append(&mut self, other: &mut Self) {
+        self.reserve(other.len);
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                other.ptr.as_ptr(),
+                self.ptr.as_ptr().add(self.len),
+                other.len,
+            )
+        };
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// Replaces the elements in `range` with the `replace_with` items, returning the removed
+    /// elements.
+    ///
+    /// Unlike `std::Vec::splice`, the replacement is done eagerly - the `replace_with` is
+    /// collected before the vector is modified, so the vector is left untouched if it panics.
+    pub fn splice<I>(&mut self, range: impl RangeBounds<usize>, replace_with: I) -> IntoIter<T, A>
+    where
+        I: IntoIterator<Item = T>,
+        A: Clone,
+    {
+        let Range { start, end } = self::range(range, self.len);
+        let mut replace_with = {
+            let mut items = Vector::new_in(self.alloc.clone());
+            items.extend(replace_with);
+            items
+        };
+
+        let removed_len = end - start;
+        let tail_len = self.len - end;
+        if replace_with.len > removed_len {
+            self.reserve(replace_with.len - removed_len);
+        }
+
+        let mut removed = Vector::with_capacity_in(removed_len, self.alloc.clone());
+        unsafe {
+            let ptr = self.ptr.as_ptr();
+            std::ptr::copy_nonoverlapping(ptr.add(start), removed.ptr.as_ptr(), removed_len);
+            std::ptr::copy(ptr.add(end), ptr.add(start + replace_with.len), tail_len);
+            std::ptr::copy_nonoverlapping(
+                replace_with.ptr.as_ptr(),
+                ptr.add(start),
+                replace_with.len,
+            );
+        }
+
+        removed.len = removed_len;
+        self.len = start + replace_with.len + tail_len;
+        replace_with.len = 0;
+
+        removed.into_iter()
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for Vector<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut iter = iter.into_iter();
+
+        match iter.size_hint() {
+            (low, Some(high)) if low == high => {
+                // Exact size - the whole space is reserved upfront, and items are written
+                // without checking the capacity
+                self.reserve(low);
+                for item in iter.by_ref().take(low) {
+                    unsafe { self.ptr.as_ptr().add(self.len).write(item) };
+                    self.len += 1;
+                }
+
+                // Just in case the iterator lied about its size
+                for item in iter {
+                    self.push(item);
+                }
+            }
+            (low, _) => {
+                self.reserve(low);
+                for item in iter {
+                    self.push(item);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a, A: Allocator> Extend<&'a T> for Vector<T, A> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    fn vector<T>(items: impl IntoIterator<Item = T>) -> Vector<T> {
+        items.into_iter().collect()
+    }
+
+    #[test]
+    fn extend() {
+        let mut v = vector([1, 2]);
+        v.extend([3, 4, 5]);
+        assert_eq!(&*v, [1, 2, 3, 4, 5]);
+
+        v.extend((6..20).filter(|i| i % 2 == 0));
+        assert_eq!(&*v, [1, 2, 3, 4, 5, 6, 8, 10, 12, 14, 16, 18]);
+
+        let mut v: Vector<()> = Vector::new();
+        v.extend(std::iter::repeat_n((), 10));
+        assert_eq!(v.len(), 10);
+    }
+
+    #[test]
+    fn extend_ref() {
+        let mut v = vector([1, 2]);
+        v.extend(&[3, 4]);
+        v.extend([5, 6].iter());
+        assert_eq!(&*v, [1, 2, 3, 4, 5, 6]);
+
+        let mut v = vector(["a".to_owned()]);
+        v.extend_from_slice(&["b".to_owned(), "c".to_owned()]);
+        assert_eq!(&*v, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn resize() {
+        let mut v = vector(["a".to_owned()]);
+        v.resize(3, "b".to_owned());
+        assert_eq!(&*v, ["a", "b", "b"]);
+
+        v.resize(1, "c".to_owned());
+        assert_eq!(&*v, ["a"]);
+    }
+
+    #[test]
+    fn truncate() {
+        let item = Rc::new(());
+        let mut v = vector(std::iter::repeat_n(item.clone(), 5));
+
+        v.truncate(10);
+        assert_eq!(v.len(), 5);
+
+        v.truncate(2);
+        assert_eq!(v.len(), 2);
+        assert_eq!(Rc::strong_count(&item), 3);
+
+        v.clear();
+        assert!(v.is_empty());
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn resize_with() {
+        let mut v = vector([1, 2]);
+        let mut next = 3;
+        v.resize_with(5, || {
+            next += 1;
+            next - 1
+        });
+        assert_eq!(&*v, [1, 2, 3, 4, 5]);
+
+        v.resize_with(1, || unreachable!());
+        assert_eq!(&*v, [1]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut v = vector(0..10);
+        v.retain(|i| i % 3 != 0);
+        assert_eq!(&*v, [1, 2, 4, 5, 7, 8]);
+
+        v.retain_mut(|i| {
+            *i *= 2;
+            *i > 5
+        });
+        assert_eq!(&*v, [8, 10, 14, 16]);
+
+        let mut v = vector((0..10).map(|i| i.to_string()));
+        v.retain(|i| i.len() > 1);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn retain_panic() {
+        let item = Rc::new(());
+        let mut v = vector((0..10).map(|i| (i, item.clone())));
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            v.retain(|(i, _)| match i {
+                5 => panic!(),
+                i => i % 2 == 0,
+            })
+        }));
+
+        assert!(res.is_err());
+        let items: Vec<_> = v.iter().map(|(i, _)| *i).collect();
+        assert_eq!(items, [0, 2, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(Rc::strong_count(&item), 9);
+
+        drop(v);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn dedup() {
+        let mut v = vector([1, 1, 2, 3, 3, 3, 1, 4, 4]);
+        v.dedup();
+        assert_eq!(&*v, [1, 2, 3, 1, 4]);
+
+        let mut v = vector([10, 11, 20, 21, 22, 30, 15]);
+        v.dedup_by_key(|i| *i / 10);
+        assert_eq!(&*v, [10, 20, 30, 15]);
+
+        let mut v = vector(["a", "A", "b", "B", "c"].map(str::to_owned));
+        v.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        assert_eq!(&*v, ["a", "b", "c"]);
+
+        let mut v: Vector<u32> = vector([]);
+        v.dedup();
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn dedup_panic() {
+        let item = Rc::new(());
+        let mut v = vector([0, 0, 1, 1, 2, 2].map(|i| (i, item.clone())));
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            v.dedup_by(|(a, _), (b, _)| match a {
+                2 => panic!(),
+                _ => a == b,
+            })
+        }));
+
+        assert!(res.is_err());
+        let items: Vec<_> = v.iter().map(|(i, _)| *i).collect();
+        assert_eq!(items, [0, 1, 2, 2]);
+        assert_eq!(Rc::strong_count(&item), 5);
+    }
+
+    #[test]
+    fn split_off() {
+        let mut v = vector(0..6);
+        let tail = v.split_off(4);
+        assert_eq!(&*v, [0, 1, 2, 3]);
+        assert_eq!(&*tail, [4, 5]);
+
+        let tail = v.split_off(4);
+        assert!(tail.is_empty());
+
+        let tail = v.split_off(0);
+        assert!(v.is_empty());
+        assert_eq!(&*tail, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn append() {
+        let mut v = vector(0..3);
+        let mut other = vector(3..6);
+        v.append(&mut other);
+        assert_eq!(&*v, [0, 1, 2, 3, 4, 5]);
+        assert!(other.is_empty());
+
+        v.append(&mut other);
+        assert_eq!(v.len(), 6);
+    }
+
+    #[test]
+    fn splice() {
+        let mut v = vector((0..6).map(|i| i.to_string()));
+
+        let removed: Vec<_> = v.splice(1..3, ["a".to_owned()]).collect();
+        assert_eq!(removed, ["1", "2"]);
+        assert_eq!(&*v, ["0", "a", "3", "4", "5"]);
+
+        let removed: Vec<_> = v.splice(4.., ["b", "c", "d"].map(str::to_owned)).collect();
+        assert_eq!(removed, ["5"]);
+        assert_eq!(&*v, ["0", "a", "3", "4", "b", "c", "d"]);
+
+        let removed: Vec<_> = v.splice(..0, ["e".to_owned()]).collect();
+        assert!(removed.is_empty());
+        assert_eq!(&*v, ["e", "0", "a", "3", "4", "b", "c", "d"]);
+
+        let removed = v.splice(.., []);
+        assert_eq!(removed.count(), 8);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn splice_panic() {
+        let mut v = vector(0..4);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            v.splice(1..2, (0..3).map(|_| -> u32 { panic!() }));
+        }));
+
+        assert!(res.is_err());
+        assert_eq!(&*v, [0, 1, 2, 3]);
+    }
+}
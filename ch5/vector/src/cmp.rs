@@ -0,0 +1,106 @@
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+use crate::{Allocator, Vector};
+
+impl<T, U, A, B> PartialEq<Vector<U, B>> for Vector<T, A>
+where
+    T: PartialEq<U>,
+    A: Allocator,
+    B: Allocator,
+{
+    fn eq(&self, other: &Vector<U, B>) -> bool {
+        **self == **other
+    }
+}
+
+impl<T, U, A: Allocator> PartialEq<[U]> for Vector<T, A>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &[U]) -> bool {
+        **self == *other
+    }
+}
+
+impl<T, U, A: Allocator> PartialEq<&[U]> for Vector<T, A>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &&[U]) -> bool {
+        **self == **other
+    }
+}
+
+impl<T, U, A: Allocator, const N: usize> PartialEq<[U; N]> for Vector<T, A>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &[U; N]) -> bool {
+        **self == *other
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Vector<T, A> {}
+
+impl<T: PartialOrd, A: Allocator, B: Allocator> PartialOrd<Vector<T, B>> for Vector<T, A> {
+    fn partial_cmp(&self, other: &Vector<T, B>) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for Vector<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+/// Hashes the same way as the slice, so it is consistent with `Borrow<[T]>`
+impl<T: Hash, A: Allocator> Hash for Vector<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{vector, Vector};
+    use std::collections::{BTreeSet, HashMap};
+
+    #[test]
+    fn eq() {
+        let v = vector![1, 2, 3];
+        assert_eq!(v, vector![1, 2, 3]);
+        assert_ne!(v, vector![1, 2]);
+        assert_eq!(v, [1, 2, 3]);
+        assert_eq!(v, &[1, 2, 3][..]);
+        assert_eq!(v, *[1, 2, 3].as_slice());
+
+        let v = vector!["a".to_owned()];
+        assert_eq!(v, ["a"]);
+    }
+
+    #[test]
+    fn ord() {
+        assert!(vector![1, 2, 3] < vector![1, 2, 4]);
+        assert!(vector![1, 2] < vector![1, 2, 0]);
+        assert!(vector![2] > vector![1, 2, 0]);
+
+        let set: BTreeSet<_> = [vector![3], vector![1, 2], vector![1]]
+            .into_iter()
+            .collect();
+        let items: Vec<_> = set.into_iter().collect();
+        assert_eq!(items, [vector![1], vector![1, 2], vector![3]]);
+    }
+
+    #[test]
+    fn hash() {
+        let mut map = HashMap::new();
+        map.insert(vector![1, 2], "a");
+        map.insert(vector![3], "b");
+
+        assert_eq!(map.get(&vector![1, 2]), Some(&"a"));
+        assert_eq!(map.get(&[3][..]), Some(&"b"));
+        assert_eq!(map.get(&Vector::new()), None);
+    }
+}
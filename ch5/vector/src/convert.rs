@@ -0,0 +1,127 @@
+use std::borrow::{Borrow, BorrowMut};
+use std::mem::ManuallyDrop;
+
+use crate::{Allocator, Vector};
+
+/// Takes over the buffer of the `Vec` - they are both allocated with the global allocator as
+/// arrays of `T`, so no copy is needed
+impl<T> From<Vec<T>> for Vector<T> {
+    fn from(vec: Vec<T>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        unsafe { Self::from_raw_parts(vec.as_mut_ptr(), vec.len(), vec.capacity()) }
+    }
+}
+
+/// Gives the buffer to the `Vec`, without copying
+impl<T> From<Vector<T>> for Vec<T> {
+    fn from(vec: Vector<T>) -> Self {
+        let (ptr, len, cap) = vec.into_raw_parts();
+        unsafe { Vec::from_raw_parts(ptr, len, cap) }
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vector<T> {
+    fn from(arr: [T; N]) -> Self {
+        let arr = ManuallyDrop::new(arr);
+        let mut this = Self::with_capacity(N);
+        // Safety:
+        // The `this` has capacity for `N` items, and the items are moved out of the `arr`, which
+        // is never dropped
+        unsafe { std::ptr::copy_nonoverlapping(arr.as_ptr(), this.ptr.as_ptr(), N) };
+        this.len = N;
+        this
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vector<T> {
+    fn from(slice: &[T]) -> Self {
+        let mut this = Self::with_capacity(slice.len());
+        this.extend_from_slice(slice);
+        this
+    }
+}
+
+impl<T, A: Allocator> AsRef<[T]> for Vector<T, A> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> AsMut<[T]> for Vector<T, A> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> Borrow<[T]> for Vector<T, A> {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, A: Allocator> BorrowMut<[T]> for Vector<T, A> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from() {
+        let v = Vector::from(vec!["a".to_owned(), "b".to_owned()]);
+        assert_eq!(v, ["a", "b"]);
+
+        let v = Vector::from(["a".to_owned(), "b".to_owned()]);
+        assert_eq!(v, ["a", "b"]);
+
+        let v = Vector::from(&["a".to_owned(), "b".to_owned()][..]);
+        assert_eq!(v, ["a", "b"]);
+
+        let v: Vector<u32> = Vector::from([]);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn vec_interop() {
+        let mut vec = Vec::with_capacity(10);
+        vec.push("a".to_owned());
+        vec.push("b".to_owned());
+        let ptr = vec.as_ptr();
+
+        let mut v = Vector::from(vec);
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v.capacity(), 10);
+        v.push("c".to_owned());
+
+        let vec = Vec::from(v);
+        assert_eq!(vec.as_ptr(), ptr);
+        assert_eq!(vec.capacity(), 10);
+        assert_eq!(vec, ["a", "b", "c"]);
+
+        let v = Vector::from(Vec::<u32>::new());
+        assert_eq!(v.capacity(), 0);
+        let vec = Vec::from(v);
+        assert!(vec.is_empty());
+
+        let v = Vector::from(vec![(); 3]);
+        assert_eq!(v.len(), 3);
+        assert_eq!(Vec::from(v).len(), 3);
+    }
+
+    #[test]
+    fn as_ref() {
+        fn sum(items: impl AsRef<[u32]>) -> u32 {
+            items.as_ref().iter().sum()
+        }
+
+        let mut v = Vector::from([1, 2, 3]);
+        assert_eq!(sum(&v), 6);
+
+        v.as_mut()[0] = 4;
+        let slice: &[u32] = v.borrow();
+        assert_eq!(slice, [4, 2, 3]);
+    }
+}
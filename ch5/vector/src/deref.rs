@@ -1,6 +1,6 @@
-use super::Vector;
+use super::{Allocator, Vector};
 
-impl<T> std::ops::Deref for Vector<T> {
+impl<T, A: Allocator> std::ops::Deref for Vector<T, A> {
     type Target = [T];
 
     fn deref(&self) -> &Self::Target {
@@ -8,12 +8,34 @@ impl<T> std::ops::Deref for Vector<T> {
     }
 }
 
-impl<T> std::ops::DerefMut for Vector<T> {
+impl<T, A: Allocator> std::ops::DerefMut for Vector<T, A> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
     }
 }
 
+impl<T, I, A> std::ops::Index<I> for Vector<T, A>
+where
+    I: std::slice::SliceIndex<[T]>,
+    A: Allocator,
+{
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        &(**self)[index]
+    }
+}
+
+impl<T, I, A> std::ops::IndexMut<I> for Vector<T, A>
+where
+    I: std::slice::SliceIndex<[T]>,
+    A: Allocator,
+{
+    fn index_mut(&mut self, index: I) -> &mut Self::Output {
+        &mut (**self)[index]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +61,15 @@ mod tests {
         v[0] = 10;
         assert_eq!(v[0], 10);
     }
+
+    #[test]
+    fn index_range() {
+        let mut v: Vector<u64> = (0..6).collect();
+        assert_eq!(v[1..3], [1, 2]);
+        assert_eq!(v[4..], [4, 5]);
+        assert_eq!(v[..=1], [0, 1]);
+
+        v[2..4].fill(0);
+        assert_eq!(v[..], [0, 1, 0, 0, 4, 5]);
+    }
 }
@@ -1,58 +1,93 @@
+use std::iter::FusedIterator;
+use std::marker::PhantomData;
+use std::ops::{Range, RangeBounds};
 use std::ptr::NonNull;
 
-use crate::Vector;
+use crate::raw_iter::RawIter;
 
+/// Converts the range bounds to the range of indices, panicking if it does not fit in `0..len`
+pub(crate) fn range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    use std::ops::Bound;
+
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => *start + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(end) => *end + 1,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        panic!("Index out of bounds");
+    }
+
+    start..end
+}
+
+/// Safety:
+/// While the `Drain` lives, the `len` of the drained buffer is shrinked to the drain start, so
+/// leaking it never exposes moved-from elements. The `tail_len` elements starting at `tail` are
+/// restored when the `Drain` is dropped.
 pub struct Drain<'vec, T> {
-    vec: &'vec mut Vector<T>,
-    start: NonNull<T>,
-    curr: NonNull<T>,
-    end: *const T,
+    ptr: NonNull<T>,
+    len: &'vec mut usize,
+    iter: RawIter<T>,
+    tail: usize,
+    tail_len: usize,
+    _marker: PhantomData<&'vec mut [T]>,
 }
 
 impl<'vec, T> Drain<'vec, T> {
-    pub(crate) fn new(vec: &'vec mut Vector<T>, range: impl std::ops::RangeBounds<usize>) -> Self {
-        use std::ops::Bound;
-
-        let start = match range.start_bound() {
-            Bound::Included(start) => *start,
-            Bound::Excluded(start) => *start + 1,
-            Bound::Unbounded => 0,
-        };
+    /// Safety:
+    /// `ptr` has to point to the buffer with `*len` initialized elements, which stays valid and is
+    /// not accessed otherwise for the `'vec` lifetime
+    pub(crate) unsafe fn new(
+        ptr: NonNull<T>,
+        len: &'vec mut usize,
+        range: impl RangeBounds<usize>,
+    ) -> Self {
+        let Range { start, end } = self::range(range, *len);
 
-        let end = match range.end_bound() {
-            Bound::Included(end) => *end + 1,
-            Bound::Excluded(end) => *end,
-            Bound::Unbounded => vec.len(),
-        };
-        let end = end.min(vec.len());
+        let tail_len = *len - end;
+        *len = start;
 
-        let start = unsafe { NonNull::new(vec.ptr.as_ptr().add(start)).unwrap() };
-        let end = unsafe { vec.ptr.as_ptr().add(end) };
+        // Safety:
+        // `start <= end <= len`, so all the elements in range are initialized
+        let iter = unsafe { RawIter::new(ptr.add(start), end - start) };
 
         Self {
-            start,
-            end,
-            curr: start,
-            vec,
+            ptr,
+            len,
+            iter,
+            tail: end,
+            tail_len,
+            _marker: PhantomData,
         }
     }
+
+    /// Returns the items which are not yet yielded
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.iter.as_mut_slice()
+    }
 }
 
 impl<T> Iterator for Drain<'_, T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr.as_ptr() as *const _ != self.end {
-            let item = unsafe { self.curr.as_ptr().read() };
-            self.curr = unsafe { NonNull::new(self.curr.as_ptr().add(1)).unwrap() };
-            Some(item)
-        } else {
-            None
-        }
+        self.iter.next()
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = unsafe { self.end.offset_from(self.curr.as_ptr()) as _ };
+        let len = self.iter.len();
         (len, Some(len))
     }
 
@@ -61,16 +96,67 @@ impl<T> Iterator for Drain<'_, T> {
     }
 }
 
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> FusedIterator for Drain<'_, T> {}
+
 impl<T> Drop for Drain<'_, T> {
     fn drop(&mut self) {
-        let len = unsafe { self.end.offset_from(self.curr.as_ptr()) as usize };
-        let slice = unsafe { std::slice::from_raw_parts_mut(self.curr.as_ptr(), len) };
-        unsafe { std::ptr::drop_in_place(slice) }
+        self.iter.drop_remaining();
 
-        let end = unsafe { self.vec.as_ptr().add(self.vec.len) };
-        let tail_len = unsafe { end.offset_from(self.end) } as usize;
-        unsafe { std::ptr::copy(self.end, self.start.as_ptr(), tail_len) };
+        let start = *self.len;
+        unsafe {
+            std::ptr::copy(
+                self.ptr.as_ptr().add(self.tail),
+                self.ptr.as_ptr().add(start),
+                self.tail_len,
+            )
+        };
+
+        *self.len = start + self.tail_len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{vector, Vector};
+
+    #[test]
+    fn double_ended() {
+        let mut v: Vector<_> = (0..10).map(|i| i.to_string()).collect();
+
+        let mut drain = v.drain(2..7);
+        assert_eq!(drain.len(), 5);
+        assert_eq!(drain.next_back(), Some("6".to_owned()));
+        assert_eq!(drain.next(), Some("2".to_owned()));
+        assert_eq!(drain.as_slice(), ["3", "4", "5"]);
+
+        drain.as_mut_slice()[0].push('!');
+        assert_eq!(drain.next(), Some("3!".to_owned()));
+        assert_eq!(drain.len(), 2);
+        drop(drain);
+
+        assert_eq!(v, ["0", "1", "7", "8", "9"]);
+
+        let drained: Vec<_> = v.drain(1..4).rev().collect();
+        assert_eq!(drained, ["8", "7", "1"]);
+        assert_eq!(v, ["0", "9"]);
+    }
 
-        self.vec.len -= len
+    #[test]
+    fn fused() {
+        let mut v = vector![(), ()];
+        let mut drain = v.drain(..);
+        assert_eq!(drain.next_back(), Some(()));
+        assert_eq!(drain.next(), Some(()));
+        assert_eq!(drain.next(), None);
+        assert_eq!(drain.next_back(), None);
+        assert_eq!(drain.next(), None);
     }
 }
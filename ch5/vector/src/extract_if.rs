@@ -0,0 +1,106 @@
+use crate::bulk::Compact;
+
+/// Iterator removing and yielding the items matching the predicate.
+///
+/// Items which are not yielded are compacted in place while iterating. If the iterator is
+/// dropped early or the predicate panics, the remaining items are kept untouched.
+pub struct ExtractIf<'vec, T, F> {
+    compact: Compact<'vec, T>,
+    end: usize,
+    pred: F,
+}
+
+impl<'vec, T, F> ExtractIf<'vec, T, F> {
+    /// Safety:
+    /// `compact` has to be created for the range ending at `end <= original`
+    pub(crate) unsafe fn new(compact: Compact<'vec, T>, end: usize, pred: F) -> Self {
+        Self { compact, end, pred }
+    }
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.compact.read < self.end {
+            let item = unsafe { &mut *self.compact.ptr.add(self.compact.read) };
+            if (self.pred)(item) {
+                return Some(unsafe { self.compact.take() });
+            }
+
+            unsafe { self.compact.keep() };
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.compact.read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vector;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::rc::Rc;
+
+    #[test]
+    fn extract_if() {
+        let mut v: Vector<_> = (0..10).collect();
+        let evens: Vec<_> = v.extract_if(.., |i| *i % 2 == 0).collect();
+        assert_eq!(evens, [0, 2, 4, 6, 8]);
+        assert_eq!(&*v, [1, 3, 5, 7, 9]);
+
+        let mut v: Vector<_> = (0..10).collect();
+        let evens: Vec<_> = v.extract_if(3..7, |i| *i % 2 == 0).collect();
+        assert_eq!(evens, [4, 6]);
+        assert_eq!(&*v, [0, 1, 2, 3, 5, 7, 8, 9]);
+
+        let mut v: Vector<_> = (0..10).map(|i| i.to_string()).collect();
+        let all: Vec<_> = v.extract_if(.., |_| true).collect();
+        assert_eq!(all.len(), 10);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn early_drop() {
+        let mut v: Vector<_> = (0..10).map(|i| i.to_string()).collect();
+
+        let mut iter = v.extract_if(.., |i| i.parse::<u32>().unwrap() % 3 == 0);
+        assert_eq!(iter.next(), Some("0".to_owned()));
+        assert_eq!(iter.next(), Some("3".to_owned()));
+        drop(iter);
+
+        assert_eq!(&*v, ["1", "2", "4", "5", "6", "7", "8", "9"]);
+
+        let mut v: Vector<_> = (0..10).collect();
+        std::mem::forget(v.extract_if(.., |_| true));
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn predicate_panic() {
+        let item = Rc::new(());
+        let mut v: Vector<_> = (0..10).map(|i| (i, item.clone())).collect();
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            v.extract_if(.., |(i, _)| match i {
+                5 => panic!(),
+                i => *i % 2 == 0,
+            })
+            .for_each(drop)
+        }));
+
+        assert!(res.is_err());
+        let items: Vec<_> = v.iter().map(|(i, _)| *i).collect();
+        assert_eq!(items, [1, 3, 5, 6, 7, 8, 9]);
+        assert_eq!(Rc::strong_count(&item), 8);
+
+        drop(v);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
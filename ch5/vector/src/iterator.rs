@@ -1,40 +1,45 @@
-use std::alloc::{dealloc, Layout};
+use std::iter::FusedIterator;
 use std::mem::ManuallyDrop;
-use std::ptr::NonNull;
 
-use crate::Vector;
+use crate::raw_iter::RawIter;
+use crate::{Allocator, Global, Vector};
 
-pub struct IntoIter<T> {
-    vec: ManuallyDrop<Vector<T>>,
-    curr: NonNull<T>,
-    end: *const T,
+pub struct IntoIter<T, A: Allocator = Global> {
+    vec: ManuallyDrop<Vector<T, A>>,
+    iter: RawIter<T>,
 }
 
-impl<T> IntoIter<T> {
-    fn new(vec: Vector<T>) -> Self {
-        Self {
-            curr: vec.ptr,
-            end: unsafe { vec.ptr.as_ptr().add(vec.len) },
-            vec: ManuallyDrop::new(vec),
-        }
+impl<T, A: Allocator> IntoIter<T, A> {
+    fn new(vec: Vector<T, A>) -> Self {
+        let mut vec = ManuallyDrop::new(vec);
+        let len = std::mem::take(&mut vec.len);
+        // Safety:
+        // The `len` elements are moved from the vector into the iterator, the vector itself is
+        // left with no initialized elements, so it is only responsible for its buffer now.
+        let iter = unsafe { RawIter::new(vec.ptr, len) };
+
+        Self { vec, iter }
+    }
+
+    /// Returns the items which are not yet yielded
+    pub fn as_slice(&self) -> &[T] {
+        self.iter.as_slice()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.iter.as_mut_slice()
     }
 }
 
-impl<T> Iterator for IntoIter<T> {
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.curr.as_ptr() as *const _ != self.end {
-            let item = unsafe { self.curr.as_ptr().read() };
-            self.curr = unsafe { NonNull::new(self.curr.as_ptr().add(1)).unwrap() };
-            Some(item)
-        } else {
-            None
-        }
+        self.iter.next()
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = unsafe { self.end.offset_from(self.curr.as_ptr()) as _ };
+        let len = self.iter.len();
         (len, Some(len))
     }
 
@@ -43,30 +48,50 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> IntoIterator for Vector<T> {
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator> FusedIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator> IntoIterator for Vector<T, A> {
     type Item = T;
-    type IntoIter = IntoIter<T>;
+    type IntoIter = IntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         IntoIter::new(self)
     }
 }
 
-impl<T> Drop for IntoIter<T> {
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
     fn drop(&mut self) {
-        let len = unsafe { self.end.offset_from(self.curr.as_ptr()) as usize };
-        let slice = unsafe { std::slice::from_raw_parts_mut(self.curr.as_ptr(), len) };
-        unsafe { std::ptr::drop_in_place(slice) }
-
-        if self.vec.cap > 0 {
-            let layout = Layout::array::<T>(self.vec.cap).unwrap();
-            // Safety:
-            // The layout for `ptr` is guaranted to be an array for `self.cap` alements of type `T`.
-            // It is also checked `self.cap` is non-zero.
-            unsafe {
-                dealloc(self.vec.ptr.as_ptr() as _, layout);
-            }
-        }
+        self.iter.drop_remaining();
+
+        // Safety:
+        // The vector has no initialized elements, so dropping it only frees the buffer
+        unsafe { ManuallyDrop::drop(&mut self.vec) }
+    }
+}
+
+/// Reuses the buffer of the iterator, moving the remaining items to its beginning - unlike
+/// `collect`, which allocates a new one
+impl<T, A: Allocator> From<IntoIter<T, A>> for Vector<T, A> {
+    fn from(iter: IntoIter<T, A>) -> Self {
+        let mut iter = ManuallyDrop::new(iter);
+        let remaining = iter.iter.as_mut_slice();
+        let (src, len) = (remaining.as_mut_ptr(), remaining.len());
+
+        // Safety:
+        // The iterator is never dropped, so the vector and the remaining items are moved out of
+        // it. The vector has no initialized items, so they are moved to its front.
+        let mut vec = unsafe { ManuallyDrop::take(&mut iter.vec) };
+        unsafe { std::ptr::copy(src, vec.ptr.as_ptr(), len) };
+        vec.len = len;
+        vec
     }
 }
 
@@ -75,15 +100,74 @@ impl<A> FromIterator<A> for Vector<A> {
     where
         T: IntoIterator<Item = A>,
     {
-        let iter = iter.into_iter();
-        let (hint_low, hint_high) = iter.size_hint();
-        let hint = hint_high.unwrap_or(hint_low);
-        let mut this = Self::with_capacity(hint);
+        let mut this = Self::new();
+        this.extend(iter);
+        this
+    }
+}
 
-        for elem in iter {
-            this.push(elem);
-        }
+#[cfg(test)]
+mod tests {
+    use crate::{vector, Vector};
+    use std::rc::Rc;
 
-        this
+    #[test]
+    fn double_ended() {
+        let v: Vector<_> = (0..6).map(|i| i.to_string()).collect();
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next_back(), Some("5".to_owned()));
+        assert_eq!(iter.next(), Some("0".to_owned()));
+        assert_eq!(iter.as_slice(), ["1", "2", "3", "4"]);
+
+        iter.as_mut_slice()[3].push('!');
+        assert_eq!(iter.next_back(), Some("4!".to_owned()));
+        assert_eq!(iter.len(), 3);
+
+        let items: Vec<_> = iter.rev().collect();
+        assert_eq!(items, ["3", "2", "1"]);
+    }
+
+    #[test]
+    fn fused() {
+        let mut iter = vector![1].into_iter();
+        assert_eq!(iter.next_back(), Some(1));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn from_into_iter_reuses_buffer() {
+        let item = Rc::new(());
+        let v: Vector<_> = (0..10).map(|i| (i, item.clone())).collect();
+        let ptr = v.as_ptr();
+        let cap = v.capacity();
+
+        let mut iter = v.into_iter();
+        iter.next();
+        iter.next_back();
+
+        let v = Vector::from(iter);
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v.capacity(), cap);
+        let items: Vec<_> = v.iter().map(|(i, _)| *i).collect();
+        assert_eq!(items, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(Rc::strong_count(&item), 9);
+
+        drop(v);
+        assert_eq!(Rc::strong_count(&item), 1);
+
+        // Non-static items
+        let s = "a".to_owned();
+        let v = vector![s.as_str(), "b"];
+        let ptr = v.as_ptr();
+        let v = Vector::from(v.into_iter());
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v, ["a", "b"]);
+
+        let v: Vector<u32> = vector![1, 2, 3].into_iter().map(|i| i * 2).collect();
+        assert_eq!(v, [2, 4, 6]);
     }
 }
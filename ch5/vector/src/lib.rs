@@ -1,98 +1,283 @@
-use std::alloc::{alloc, dealloc, realloc, Layout};
-use std::mem::size_of;
+use std::alloc::Layout;
+use std::mem::{size_of, ManuallyDrop, MaybeUninit};
 use std::ptr::NonNull;
 
 use drain::Drain;
+use extract_if::ExtractIf;
 
+pub use alloc::{AllocError, Allocator, Global, TryReserveError};
+pub use small_vector::SmallVector;
+
+mod alloc;
+mod bulk;
+mod cmp;
+mod convert;
 mod deref;
 mod drain;
+mod extract_if;
 mod iterator;
+mod raw_iter;
+mod small_vector;
+
+/// Creates a `Vector` containing the arguments, just like `std::vec!`
+#[macro_export]
+macro_rules! vector {
+    () => {
+        $crate::Vector::new()
+    };
+    ($elem:expr; $n:expr) => {{
+        let mut vector = $crate::Vector::new();
+        vector.resize($n, $elem);
+        vector
+    }};
+    ($($x:expr),+ $(,)?) => {
+        $crate::Vector::from([$($x),+])
+    };
+}
 
 /// Custom `std::Vec<T>` remiplementation.
 ///
 /// Safety:
 /// The whole implementation assumes that:
-/// * T is not a ZST
-/// * If `cap` is non-zero, the `ptr` is a valid pointer to `cap` elements of type `T`
+/// * If `T` is a ZST, `cap` is always `usize::MAX`, and `ptr` is dangling - nothing is ever
+///   allocated
+/// * If `cap` is non-zero and `T` is not a ZST, the `ptr` is a valid pointer to `cap` elements of
+///   type `T`, allocated with `alloc`
 /// * First `len` elements of `ptr` are properly initialized, and not yet dropped
 /// * `len <= cap`
-#[derive(Debug)]
-pub struct Vector<T> {
+pub struct Vector<T, A: Allocator = Global> {
     ptr: NonNull<T>,
     cap: usize,
     len: usize,
+    alloc: A,
 }
 
 impl<T> Vector<T> {
-    pub fn assert_zst() {
-        if size_of::<T>() == 0 {
-            panic!("ZSTs are not supported");
-        }
+    pub fn new() -> Self {
+        Self::new_in(Global)
     }
 
-    pub fn new() -> Self {
-        Self::assert_zst();
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Creates the vector directly from its raw parts.
+    ///
+    /// # Safety
+    /// * `ptr` has to be allocated by the global allocator as an array of `cap` elements of type
+    ///   `T`, or be dangling if `cap` is zero or `T` is a ZST
+    /// * First `len` elements of `ptr` have to be initialized, and `len <= cap`
+    ///
+    /// The ownership of the buffer is transferred to the vector.
+    pub unsafe fn from_raw_parts(ptr: *mut T, len: usize, cap: usize) -> Self {
+        Self::from_raw_parts_in(ptr, len, cap, Global)
+    }
+
+    /// Decomposes the vector into its raw parts - the pointer, the length, and the capacity.
+    ///
+    /// The caller becomes responsible for the buffer, which can be turned back into the vector
+    /// with `Vector::from_raw_parts`.
+    pub fn into_raw_parts(self) -> (*mut T, usize, usize) {
+        let (ptr, len, cap, _) = self.into_raw_parts_with_alloc();
+        (ptr, len, cap)
+    }
+
+    pub fn into_boxed_slice(mut self) -> Box<[T]> {
+        // `Box` deallocates with the layout of the slice, so there cannot be any spare capacity
+        self.shrink_to_fit();
+        let boxed = unsafe { Box::from_raw(&mut self as &mut [T]) };
+        std::mem::forget(self);
+        boxed
+    }
+}
+
+impl<T, A: Allocator> Vector<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { 0 };
 
         Self {
             ptr: NonNull::dangling(),
-            cap: 0,
+            cap,
             len: 0,
+            alloc,
         }
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
-        Self::assert_zst();
-
-        let mut this = Self::new();
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut this = Self::new_in(alloc);
         this.grow_to(cap);
         this
     }
 
+    /// Creates the vector directly from its raw parts and the allocator.
+    ///
+    /// # Safety
+    /// * `ptr` has to be allocated by the `alloc` as an array of `cap` elements of type `T`, or be
+    ///   dangling if `cap` is zero or `T` is a ZST
+    /// * First `len` elements of `ptr` have to be initialized, and `len <= cap`
+    pub unsafe fn from_raw_parts_in(ptr: *mut T, len: usize, cap: usize, alloc: A) -> Self {
+        let cap = if size_of::<T>() == 0 { usize::MAX } else { cap };
+
+        Self {
+            ptr: NonNull::new_unchecked(ptr),
+            cap,
+            len,
+            alloc,
+        }
+    }
+
+    pub fn into_raw_parts_with_alloc(self) -> (*mut T, usize, usize, A) {
+        let this = ManuallyDrop::new(self);
+        // Safety:
+        // The vector is never dropped, so the allocator is moved out of it
+        let alloc = unsafe { std::ptr::read(&this.alloc) };
+        (this.ptr.as_ptr(), this.len, this.cap, alloc)
+    }
+
+    /// Returns raw pointer to the buffer. It is valid for reads of `len` elements, and never null.
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns raw pointer to the buffer. It is valid for writes of `cap` elements, and never
+    /// null.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Returns the uninitialized part of the buffer, which can be filled before calling `set_len`
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        // For ZSTs `cap - len` may be huge, but no memory is ever touched anyway
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.ptr.as_ptr().add(self.len) as *mut MaybeUninit<T>,
+                self.cap - self.len,
+            )
+        }
+    }
+
+    /// Changes the length of the vector without touching its elements.
+    ///
+    /// # Safety
+    /// `new_len <= capacity()`, and first `new_len` elements have to be initialized
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.len = new_len;
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
     fn grow_to(&mut self, newcap: usize) {
+        if let Err(err) = self.try_grow_to(newcap) {
+            err.handle()
+        }
+    }
+
+    fn try_grow_to(&mut self, newcap: usize) -> Result<(), TryReserveError> {
+        // ZSTs capacity is always `usize::MAX`, so they never need to grow
         match self.cap {
             0 if newcap > 0 => {
-                self.cap = newcap;
-                let layout = Layout::array::<T>(self.cap).unwrap();
-                // Safety:
+                let layout = Layout::array::<T>(newcap)?;
                 // `newcap > 0`, so layout has more than zero elements, and `T` is not ZST
-                let ptr = unsafe { alloc(layout) as _ };
-                self.ptr = NonNull::new(ptr).unwrap();
+                let ptr = self
+                    .alloc
+                    .allocate(layout)
+                    .map_err(|_| TryReserveError::AllocError { layout })?;
+                self.ptr = ptr.cast();
+                self.cap = newcap;
             }
             cap if cap < newcap => {
-                let layout = Layout::array::<T>(cap).unwrap();
-                let newlayout = Layout::array::<T>(newcap).unwrap();
+                let layout = Layout::array::<T>(cap)?;
+                let newlayout = Layout::array::<T>(newcap)?;
                 // Safety:
                 // `ptr` is allocated as an array of `self.cap` objects of type `T`, as for
                 // calculated layout. The `newcap` is non-zero as `self.cap` is also non-zero.
                 // `T` is checked to be not ZST
-                let ptr = unsafe { realloc(self.ptr.as_ptr() as _, layout, newlayout.size()) as _ };
-                self.ptr = NonNull::new(ptr).unwrap();
+                let ptr = unsafe {
+                    self.alloc
+                        .reallocate(self.ptr.cast(), layout, newlayout)
+                        .map_err(|_| TryReserveError::AllocError { layout: newlayout })?
+                };
+                self.ptr = ptr.cast();
                 self.cap = newcap;
             }
             _ => (),
         }
+
+        Ok(())
     }
 
     fn grow_for(&mut self, elements: usize) {
-        let newlen = self.len.checked_add(elements).unwrap();
+        if let Err(err) = self.try_grow_for(elements) {
+            err.handle()
+        }
+    }
+
+    /// Makes space for at least `elements` more items, growing the buffer at least twice to keep
+    /// pushes amortized O(1)
+    fn try_grow_for(&mut self, elements: usize) -> Result<(), TryReserveError> {
+        let newlen = self
+            .len
+            .checked_add(elements)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+
         if newlen > self.cap {
-            let newcap = newlen.max(self.cap.checked_mul(2).unwrap());
-            self.grow_to(newcap)
+            let newcap = newlen.max(self.cap.saturating_mul(2));
+            self.try_grow_to(newcap)?;
         }
+
+        Ok(())
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.grow_for(additional)
+    }
+
+    pub fn reserve_exact(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve_exact(additional) {
+            err.handle()
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more elements, possibly more to avoid frequent
+    /// reallocations. On failure the vector is left unchanged.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_grow_for(additional)
+    }
+
+    /// Reserves capacity for exactly `additional` more elements. On failure the vector is left
+    /// unchanged.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let newlen = self
+            .len
+            .checked_add(additional)
+            .ok_or(TryReserveError::CapacityOverflow)?;
+        self.try_grow_to(newlen)
     }
 
     pub fn shrink_to_fit(&mut self) {
-        if self.cap == 0 {
+        if self.cap == self.len || size_of::<T>() == 0 {
             return;
         }
 
         let layout = Layout::array::<T>(self.cap).unwrap();
         match self.len {
-            0 => unsafe { dealloc(self.ptr.as_ptr() as _, layout) },
+            0 => {
+                unsafe { self.alloc.deallocate(self.ptr.cast(), layout) };
+                self.ptr = NonNull::dangling();
+            }
             len => {
                 let newlayout = Layout::array::<T>(len).unwrap();
-                let ptr = unsafe { realloc(self.ptr.as_ptr() as _, layout, newlayout.size()) as _ };
-                self.ptr = NonNull::new(ptr).unwrap();
+                let ptr = unsafe { self.alloc.reallocate(self.ptr.cast(), layout, newlayout) };
+                let Ok(ptr) = ptr else {
+                    std::alloc::handle_alloc_error(newlayout)
+                };
+                self.ptr = ptr.cast();
             }
         }
 
@@ -105,6 +290,15 @@ impl<T> Vector<T> {
         self.len += 1;
     }
 
+    /// Appends an element, returning an error instead of aborting if the buffer cannot grow. The
+    /// `item` is dropped on failure.
+    pub fn try_push(&mut self, item: T) -> Result<(), TryReserveError> {
+        self.try_grow_for(1)?;
+        unsafe { self.ptr.as_ptr().add(self.len).write(item) };
+        self.len += 1;
+        Ok(())
+    }
+
     pub fn pop(&mut self) -> Option<T> {
         match self.len {
             0 => None,
@@ -120,11 +314,28 @@ impl<T> Vector<T> {
             panic!("Index out of bounds");
         }
         self.grow_for(1);
+        unsafe { self.insert_unchecked(index, element) }
+    }
+
+    /// Inserts an element, returning an error instead of aborting if the buffer cannot grow. The
+    /// `element` is dropped on failure.
+    pub fn try_insert(&mut self, index: usize, element: T) -> Result<(), TryReserveError> {
+        if index > self.len {
+            panic!("Index out of bounds");
+        }
+        self.try_grow_for(1)?;
+        unsafe { self.insert_unchecked(index, element) };
+        Ok(())
+    }
+
+    /// Safety:
+    /// `index <= len` and `len < cap`
+    unsafe fn insert_unchecked(&mut self, index: usize, element: T) {
         unsafe {
             // Safety:
             // `ptr` is a pointer with `len` elements initialized and `cap` elements valid for
-            // write, and due to `grow_for(1)`, `cap >= len + 1`.
-            // `index <= len` by validation.
+            // write, and by the function contract, `cap >= len + 1`.
+            // `index <= len` by the function contract.
             // The copy source is `(ptr+index)..(ptr+index+len-index)`, that is
             // `ptr+index..ptr+len`, all are valid for read.
             // The copy destination is `(ptr+index+1)..(ptr+index+1+len-index)`, that is
@@ -169,36 +380,460 @@ impl<T> Vector<T> {
         self.pop().unwrap()
     }
 
-    pub fn into_boxed_slice(mut self) -> Box<[T]> {
-        let boxed = unsafe { Box::from_raw(&mut self as &mut [T]) };
-        std::mem::forget(self);
-        boxed
+    pub fn drain(&mut self, range: impl std::ops::RangeBounds<usize>) -> Drain<'_, T> {
+        // Safety:
+        // The drain mutably borrows the vector, so the buffer is not accessed otherwise
+        unsafe { Drain::new(self.ptr, &mut self.len, range) }
     }
 
-    pub fn drain(&mut self, range: impl std::ops::RangeBounds<usize>) -> Drain<'_, T> {
-        Drain::new(self, range)
+    /// Removes the elements in `range` matching the `filter`, yielding them lazily. The rest of
+    /// the items are kept in their original order.
+    pub fn extract_if<F>(
+        &mut self,
+        range: impl std::ops::RangeBounds<usize>,
+        filter: F,
+    ) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let range = drain::range(range, self.len);
+        // Safety:
+        // `range.start <= range.end <= len`, as validated by `drain::range`
+        unsafe {
+            let compact = bulk::Compact::starting_at(self.ptr.as_ptr(), &mut self.len, range.start);
+            ExtractIf::new(compact, range.end, filter)
+        }
     }
 }
 
-impl<T> Default for Vector<T> {
+impl<T, A: Allocator + Default> Default for Vector<T, A> {
     fn default() -> Self {
-        Self::new()
+        Self::new_in(A::default())
     }
 }
 
-impl<T> Drop for Vector<T> {
+impl<T: Clone, A: Allocator + Clone> Clone for Vector<T, A> {
+    fn clone(&self) -> Self {
+        let mut this = Self::with_capacity_in(self.len, self.alloc.clone());
+        this.extend_from_slice(self);
+        this
+    }
+
+    /// Reuses the allocation of `self`, growing it only if the `source` is longer
+    fn clone_from(&mut self, source: &Self) {
+        self.truncate(source.len);
+        let (init, tail) = source.split_at(self.len);
+        self.clone_from_slice(init);
+        self.extend_from_slice(tail);
+    }
+}
+
+impl<T: std::fmt::Debug, A: Allocator> std::fmt::Debug for Vector<T, A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, A: Allocator> Drop for Vector<T, A> {
     fn drop(&mut self) {
         let slice = unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) };
         unsafe { std::ptr::drop_in_place(slice) };
 
-        if self.cap > 0 {
+        if self.cap > 0 && size_of::<T>() != 0 {
             let layout = Layout::array::<T>(self.cap).unwrap();
             // Safety:
             // The layout for `ptr` is guaranted to be an array for `self.cap` alements of type `T`.
-            // It is also checked `self.cap` is non-zero.
+            // It is also checked `self.cap` is non-zero, and `T` is not a ZST.
             unsafe {
-                dealloc(self.ptr.as_ptr() as _, layout);
+                self.alloc.deallocate(self.ptr.cast(), layout);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq)]
+    struct Zst;
+
+    /// ZST counting its drops
+    struct DropCount<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCount<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn zst_push_pop() {
+        let mut v = Vector::new();
+        assert_eq!(v.capacity(), usize::MAX);
+
+        for _ in 0..10 {
+            v.push(Zst);
+        }
+        assert_eq!(v.len(), 10);
+        assert_eq!(v.capacity(), usize::MAX);
+
+        for _ in 0..10 {
+            assert_eq!(v.pop(), Some(Zst));
+        }
+        assert_eq!(v.pop(), None);
+
+        let v: Vector<()> = Vector::with_capacity(10);
+        assert_eq!(v.capacity(), usize::MAX);
+    }
+
+    #[test]
+    fn zst_insert_remove() {
+        let mut v = Vector::new();
+        v.insert(0, ());
+        v.insert(1, ());
+        v.insert(0, ());
+        assert_eq!(v.len(), 3);
+
+        assert_eq!(v.remove(1), ());
+        assert_eq!(v.swap_remove(0), ());
+        assert_eq!(v.len(), 1);
+    }
+
+    #[test]
+    fn zst_drop() {
+        let drops = Cell::new(0);
+
+        let mut v = Vector::new();
+        for _ in 0..5 {
+            v.push(DropCount(&drops));
+        }
+        drop(v);
+        assert_eq!(drops.get(), 5);
+    }
+
+    #[test]
+    fn zst_into_iter() {
+        let mut v = Vector::new();
+        for _ in 0..5 {
+            v.push(Zst);
+        }
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.size_hint(), (5, Some(5)));
+        assert_eq!(iter.next(), Some(Zst));
+        assert_eq!(iter.size_hint(), (4, Some(4)));
+        assert_eq!(iter.count(), 4);
+
+        let drops = Cell::new(0);
+        let mut v = Vector::new();
+        for _ in 0..5 {
+            v.push(DropCount(&drops));
+        }
+
+        let mut iter = v.into_iter();
+        drop(iter.next());
+        assert_eq!(drops.get(), 1);
+        drop(iter);
+        assert_eq!(drops.get(), 5);
+
+        let v: Vector<[u64; 0]> = (0..7).map(|_| []).collect();
+        assert_eq!(v.into_iter().count(), 7);
+    }
+
+    #[test]
+    fn zst_drain() {
+        let drops = Cell::new(0);
+        let mut v = Vector::new();
+        for _ in 0..10 {
+            v.push(DropCount(&drops));
+        }
+
+        let mut drain = v.drain(2..6);
+        assert_eq!(drain.size_hint(), (4, Some(4)));
+        drop(drain.next());
+        drop(drain);
+
+        assert_eq!(drops.get(), 4);
+        assert_eq!(v.len(), 6);
+    }
+
+    #[test]
+    fn zst_shrink_to_fit() {
+        let mut v = Vector::new();
+        v.push(());
+        v.shrink_to_fit();
+        assert_eq!(v.capacity(), usize::MAX);
+
+        let boxed = v.into_boxed_slice();
+        assert_eq!(boxed.len(), 1);
+    }
+
+    #[test]
+    fn drain() {
+        let mut v: Vector<_> = (0..10).map(|i| i.to_string()).collect();
+
+        let drained: Vec<_> = v.drain(2..5).collect();
+        assert_eq!(drained, ["2", "3", "4"]);
+        assert_eq!(&*v, ["0", "1", "5", "6", "7", "8", "9"]);
+
+        let mut drain = v.drain(..3);
+        assert_eq!(drain.next(), Some("0".to_owned()));
+        drop(drain);
+        assert_eq!(&*v, ["6", "7", "8", "9"]);
+    }
+
+    /// Allocator tracking number of live allocations and bytes
+    #[derive(Default)]
+    struct Counting {
+        allocs: Cell<usize>,
+        bytes: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            self.allocs.set(self.allocs.get() + 1);
+            self.bytes.set(self.bytes.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.allocs.set(self.allocs.get() - 1);
+            self.bytes.set(self.bytes.get() - layout.size());
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn custom_allocator() {
+        let alloc = Counting::default();
+
+        let mut v = Vector::new_in(&alloc);
+        assert_eq!(alloc.allocs.get(), 0);
+
+        for i in 0u32..10 {
+            v.push(i);
+        }
+        assert_eq!(alloc.allocs.get(), 1);
+        assert_eq!(alloc.bytes.get(), v.capacity() * 4);
+
+        v.shrink_to_fit();
+        assert_eq!(alloc.bytes.get(), 40);
+
+        v.drain(5..);
+        v.shrink_to_fit();
+        assert_eq!(alloc.bytes.get(), 20);
+
+        let items: Vec<_> = v.into_iter().collect();
+        assert_eq!(items, [0, 1, 2, 3, 4]);
+        assert_eq!(alloc.allocs.get(), 0);
+
+        let mut v = Vector::with_capacity_in(3, &alloc);
+        v.push(1u64);
+        assert_eq!(alloc.bytes.get(), 24);
+        v.pop();
+        v.shrink_to_fit();
+        assert_eq!(alloc.allocs.get(), 0);
+
+        let mut v = Vector::new_in(&alloc);
+        v.push("1".to_owned());
+        v.push("2".to_owned());
+        drop(v);
+        assert_eq!(alloc.allocs.get(), 0);
+
+        let v: Vector<(), _> = Vector::with_capacity_in(10, &alloc);
+        assert_eq!(v.capacity(), usize::MAX);
+        assert_eq!(alloc.allocs.get(), 0);
+
+        // Removed items are kept in the same allocator
+        let mut v = Vector::new_in(&alloc);
+        v.extend(0u32..10);
+        let removed = v.splice(2..5, [10, 11]);
+        assert_eq!(alloc.allocs.get(), 2);
+        assert_eq!(removed.collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(&*v, [0, 1, 10, 11, 5, 6, 7, 8, 9]);
+        drop(v);
+        assert_eq!(alloc.allocs.get(), 0);
+    }
+
+    /// Allocator serving allocations up to the `limit` bytes in total. Reallocations are not
+    /// in-place, so growing needs space for both old and new buffer.
+    struct Limited {
+        limit: usize,
+        used: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Limited {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            if self.used.get() + layout.size() > self.limit {
+                return Err(AllocError);
+            }
+
+            self.used.set(self.used.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.used.set(self.used.get() - layout.size());
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn try_reserve() {
+        let mut v: Vector<u64> = Vector::new();
+        assert_eq!(v.try_reserve(10), Ok(()));
+        assert!(v.capacity() >= 10);
+
+        assert_eq!(
+            v.try_reserve(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+        assert_eq!(
+            v.try_reserve(usize::MAX / 4),
+            Err(TryReserveError::CapacityOverflow)
+        );
+
+        v.push(1);
+        assert_eq!(v.try_reserve_exact(99), Ok(()));
+        assert_eq!(v.capacity(), 100);
+        assert_eq!(&*v, [1]);
+
+        let mut v: Vector<()> = Vector::new();
+        v.push(());
+        assert_eq!(
+            v.try_reserve_exact(usize::MAX),
+            Err(TryReserveError::CapacityOverflow)
+        );
+    }
+
+    #[test]
+    fn try_alloc_error() {
+        let alloc = Limited {
+            limit: 128,
+            used: Cell::new(0),
+        };
+
+        let mut v: Vector<u64, _> = Vector::new_in(&alloc);
+        assert_eq!(
+            v.try_reserve_exact(17),
+            Err(TryReserveError::AllocError {
+                layout: Layout::array::<u64>(17).unwrap()
+            })
+        );
+        assert_eq!(v.capacity(), 0);
+
+        for i in 0..8 {
+            assert_eq!(v.try_push(i), Ok(()));
+        }
+        assert!(matches!(
+            v.try_push(8),
+            Err(TryReserveError::AllocError { .. })
+        ));
+        assert!(matches!(
+            v.try_insert(0, 8),
+            Err(TryReserveError::AllocError { .. })
+        ));
+        assert_eq!(&*v, [0, 1, 2, 3, 4, 5, 6, 7]);
+
+        v.pop();
+        assert_eq!(v.try_insert(0, 8), Ok(()));
+        assert_eq!(&*v, [8, 0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn clone() {
+        let v = vector!["a".to_owned(), "b".to_owned()];
+        let cloned = v.clone();
+        assert_eq!(v, cloned);
+
+        let mut target = vector!["x".to_owned(); 5];
+        let cap = target.capacity();
+        target.clone_from(&v);
+        assert_eq!(target, v);
+        assert_eq!(target.capacity(), cap);
+
+        let mut target = Vector::new();
+        target.clone_from(&v);
+        assert_eq!(target, v);
+    }
+
+    #[test]
+    fn debug() {
+        let v = vector![1, 2, 3];
+        assert_eq!(format!("{v:?}"), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn vector_macro() {
+        let v: Vector<u32> = vector![];
+        assert!(v.is_empty());
+
+        let v = vector![1, 2, 3,];
+        assert_eq!(v, [1, 2, 3]);
+
+        let v = vector![vector![0u8; 2]; 3];
+        assert_eq!(v, [[0, 0], [0, 0], [0, 0]]);
+    }
+
+    #[test]
+    fn raw_parts() {
+        let mut v = vector!["a".to_owned(), "b".to_owned()];
+        v.reserve_exact(3);
+
+        let (ptr, len, cap) = v.into_raw_parts();
+        assert_eq!((len, cap), (2, 5));
+
+        let v = unsafe { Vector::from_raw_parts(ptr, len, cap) };
+        assert_eq!(v, ["a", "b"]);
+
+        let (ptr, len, cap) = vector![(), ()].into_raw_parts();
+        assert_eq!((len, cap), (2, usize::MAX));
+        let v = unsafe { Vector::from_raw_parts(ptr, len, 0) };
+        assert_eq!(v.capacity(), usize::MAX);
+
+        let alloc = Counting::default();
+        let mut v = Vector::new_in(&alloc);
+        v.push(1u8);
+
+        let (ptr, len, cap, alloc) = v.into_raw_parts_with_alloc();
+        assert_eq!(alloc.allocs.get(), 1);
+        let v = unsafe { Vector::from_raw_parts_in(ptr, len, cap, alloc) };
+        drop(v);
+        assert_eq!(alloc.allocs.get(), 0);
+    }
+
+    #[test]
+    fn spare_capacity() {
+        let mut v: Vector<u32> = Vector::with_capacity(4);
+        v.push(1);
+
+        let spare = v.spare_capacity_mut();
+        assert_eq!(spare.len(), 3);
+        spare[0].write(2);
+        spare[1].write(3);
+        unsafe { v.set_len(3) };
+        assert_eq!(v, [1, 2, 3]);
+
+        let ptr = v.as_mut_ptr();
+        unsafe { ptr.add(3).write(4) };
+        unsafe { v.set_len(4) };
+        assert_eq!(v, [1, 2, 3, 4]);
+        assert_eq!(unsafe { *v.as_ptr().add(3) }, 4);
+        assert!(v.spare_capacity_mut().is_empty());
+    }
+
+    #[test]
+    fn into_boxed_slice() {
+        let mut v = Vector::with_capacity(10);
+        v.push(1);
+        v.push(2);
+
+        let boxed = v.into_boxed_slice();
+        assert_eq!(&*boxed, [1, 2]);
+
+        let v: Vector<u32> = Vector::with_capacity(10);
+        assert_eq!(v.into_boxed_slice().len(), 0);
+    }
+}
@@ -0,0 +1,80 @@
+use std::mem::size_of;
+use std::ptr::NonNull;
+
+/// Iterator over the owned range of elements, shared by `IntoIter` and `Drain`.
+///
+/// Safety:
+/// * All the elements in `curr..end` are initialized and owned by the iterator
+/// * For ZSTs pointers are never dereferenced, and `end - curr` is the number of elements left
+pub(crate) struct RawIter<T> {
+    curr: NonNull<T>,
+    end: *const T,
+}
+
+impl<T> RawIter<T> {
+    /// Safety:
+    /// `ptr` has to point to `len` initialized elements, which are moved into the iterator
+    pub(crate) unsafe fn new(ptr: NonNull<T>, len: usize) -> Self {
+        let end = if size_of::<T>() == 0 {
+            ptr.as_ptr().wrapping_byte_add(len)
+        } else {
+            ptr.as_ptr().add(len)
+        };
+
+        Self { curr: ptr, end }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        if size_of::<T>() == 0 {
+            self.end.addr().wrapping_sub(self.curr.as_ptr().addr())
+        } else {
+            unsafe { self.end.offset_from(self.curr.as_ptr()) as _ }
+        }
+    }
+
+    pub(crate) fn next(&mut self) -> Option<T> {
+        if std::ptr::eq(self.curr.as_ptr(), self.end) {
+            return None;
+        }
+
+        if size_of::<T>() == 0 {
+            // ZSTs are counted down with the `end` pointer, and read from the properly aligned
+            // dangling pointer.
+            self.end = self.end.wrapping_byte_sub(1);
+            unsafe { Some(NonNull::<T>::dangling().as_ptr().read()) }
+        } else {
+            let item = unsafe { self.curr.as_ptr().read() };
+            self.curr = unsafe { self.curr.add(1) };
+            Some(item)
+        }
+    }
+
+    pub(crate) fn next_back(&mut self) -> Option<T> {
+        if std::ptr::eq(self.curr.as_ptr(), self.end) {
+            return None;
+        }
+
+        if size_of::<T>() == 0 {
+            self.end = self.end.wrapping_byte_sub(1);
+            unsafe { Some(NonNull::<T>::dangling().as_ptr().read()) }
+        } else {
+            self.end = unsafe { self.end.sub(1) };
+            unsafe { Some(self.end.read()) }
+        }
+    }
+
+    pub(crate) fn as_slice(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.curr.as_ptr(), self.len()) }
+    }
+
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.curr.as_ptr(), self.len()) }
+    }
+
+    /// Drops all the elements which were not yielded yet
+    pub(crate) fn drop_remaining(&mut self) {
+        let slice: *mut [T] = self.as_mut_slice();
+        self.end = self.curr.as_ptr();
+        unsafe { std::ptr::drop_in_place(slice) }
+    }
+}
@@ -0,0 +1,305 @@
+use std::mem::MaybeUninit;
+use std::ptr::NonNull;
+
+use crate::drain::Drain;
+use crate::Vector;
+
+mod deref;
+mod iterator;
+
+/// `Vector<T>` keeping up to `N` elements inline, and moving them to the heap when it grows
+/// bigger.
+///
+/// Safety:
+/// * In the `Inline` variant, first `len` items of the `buf` are initialized, and
+///   `len <= INLINE_CAP`
+/// * Once spilled to the heap, items are never moved back inline
+pub struct SmallVector<T, const N: usize> {
+    data: Data<T, N>,
+}
+
+enum Data<T, const N: usize> {
+    Inline {
+        buf: [MaybeUninit<T>; N],
+        len: usize,
+    },
+    Heap(Vector<T>),
+}
+
+impl<T, const N: usize> SmallVector<T, N> {
+    /// Number of items fitting inline - any number of zero-sized ones, as they take no space
+    const INLINE_CAP: usize = if size_of::<T>() == 0 { usize::MAX } else { N };
+
+    pub fn new() -> Self {
+        Self {
+            data: Data::Inline {
+                buf: [const { MaybeUninit::uninit() }; N],
+                len: 0,
+            },
+        }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        if cap <= Self::INLINE_CAP {
+            Self::new()
+        } else {
+            Self {
+                data: Data::Heap(Vector::with_capacity(cap)),
+            }
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Data::Inline { .. } => Self::INLINE_CAP,
+            Data::Heap(vec) => vec.capacity(),
+        }
+    }
+
+    /// Returns `true` if the items are stored on the heap
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Data::Heap(_))
+    }
+
+    /// Moves the items to the heap, if there is no space for `elements` more items inline
+    fn grow_for(&mut self, elements: usize) {
+        let Data::Inline { buf, len } = &mut self.data else {
+            return;
+        };
+
+        let newlen = len.checked_add(elements).unwrap();
+        if newlen <= Self::INLINE_CAP {
+            return;
+        }
+
+        let mut vec = Vector::with_capacity(newlen.max(N.saturating_mul(2)));
+        // Safety:
+        // First `len` items of `buf` are initialized, and the `vec` has capacity for at least
+        // `len` items. After the copy the items are owned by the `vec`, and the `buf` is
+        // forgotten.
+        unsafe {
+            std::ptr::copy_nonoverlapping(buf.as_ptr() as *const T, vec.ptr.as_ptr(), *len);
+        }
+        vec.len = *len;
+
+        self.data = Data::Heap(vec);
+    }
+
+    pub fn push(&mut self, item: T) {
+        self.grow_for(1);
+        match &mut self.data {
+            Data::Inline { buf, len } => {
+                // Safety:
+                // `len < INLINE_CAP` after `grow_for`, so the item fits in the `buf` (or it is
+                // zero-sized)
+                unsafe { (buf.as_mut_ptr() as *mut T).add(*len).write(item) };
+                *len += 1;
+            }
+            Data::Heap(vec) => vec.push(item),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.data {
+            Data::Inline { len: 0, .. } => None,
+            Data::Inline { buf, len } => {
+                *len -= 1;
+                Some(unsafe { (buf.as_ptr() as *const T).add(*len).read() })
+            }
+            Data::Heap(vec) => vec.pop(),
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, element: T) {
+        if index > self.len() {
+            panic!("Index out of bounds");
+        }
+        self.grow_for(1);
+
+        match &mut self.data {
+            Data::Inline { buf, len } => {
+                let ptr = buf.as_mut_ptr() as *mut T;
+                // Safety:
+                // `index <= len < INLINE_CAP`, so shifting the `ptr+index..ptr+len` items by one stays
+                // within the `buf`, and leaves `*(ptr+index)` free to write
+                unsafe {
+                    std::ptr::copy(ptr.add(index), ptr.add(index + 1), *len - index);
+                    ptr.add(index).write(element);
+                }
+                *len += 1;
+            }
+            Data::Heap(vec) => vec.insert(index, element),
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        match &mut self.data {
+            Data::Inline { buf, len } => {
+                if index >= *len {
+                    panic!("Index out of bounds");
+                }
+
+                let ptr = buf.as_mut_ptr() as *mut T;
+                let item = unsafe { ptr.add(index).read() };
+                unsafe { std::ptr::copy(ptr.add(index + 1), ptr.add(index), *len - index - 1) };
+                *len -= 1;
+                item
+            }
+            Data::Heap(vec) => vec.remove(index),
+        }
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let last_idx = self.len() - 1;
+        self.swap(index, last_idx);
+        self.pop().unwrap()
+    }
+
+    pub fn drain(&mut self, range: impl std::ops::RangeBounds<usize>) -> Drain<'_, T> {
+        match &mut self.data {
+            Data::Inline { buf, len } => {
+                let ptr = NonNull::new(buf.as_mut_ptr() as *mut T).unwrap();
+                // Safety:
+                // The drain mutably borrows the `buf`, so it is not accessed otherwise
+                unsafe { Drain::new(ptr, len, range) }
+            }
+            Data::Heap(vec) => vec.drain(range),
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVector<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVector<T, N> {
+    fn drop(&mut self) {
+        // Heap items are dropped by the `Vector`
+        if let Data::Inline { buf, len } = &mut self.data {
+            let slice = std::ptr::slice_from_raw_parts_mut(buf.as_mut_ptr() as *mut T, *len);
+            unsafe { std::ptr::drop_in_place(slice) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_pop() {
+        let mut v: SmallVector<u32, 4> = SmallVector::new();
+        assert_eq!(v.capacity(), 4);
+
+        for i in 0..4 {
+            v.push(i);
+        }
+        assert!(!v.spilled());
+        assert_eq!(&*v, [0, 1, 2, 3]);
+
+        v.push(4);
+        assert!(v.spilled());
+        assert!(v.capacity() >= 8);
+        assert_eq!(&*v, [0, 1, 2, 3, 4]);
+
+        for i in (0..5).rev() {
+            assert_eq!(v.pop(), Some(i));
+        }
+        assert_eq!(v.pop(), None);
+
+        let mut v: SmallVector<u32, 4> = SmallVector::new();
+        v.push(1);
+        assert_eq!(v.pop(), Some(1));
+        assert_eq!(v.pop(), None);
+        assert!(!v.spilled());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let v: SmallVector<u32, 4> = SmallVector::with_capacity(4);
+        assert!(!v.spilled());
+
+        let v: SmallVector<u32, 4> = SmallVector::with_capacity(5);
+        assert!(v.spilled());
+        assert_eq!(v.capacity(), 5);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut v: SmallVector<String, 4> = SmallVector::new();
+        v.insert(0, "b".to_owned());
+        v.insert(0, "a".to_owned());
+        v.insert(2, "d".to_owned());
+        v.insert(2, "c".to_owned());
+        assert!(!v.spilled());
+        assert_eq!(&*v, ["a", "b", "c", "d"]);
+
+        assert_eq!(v.remove(1), "b");
+        assert_eq!(&*v, ["a", "c", "d"]);
+
+        v.insert(1, "b".to_owned());
+        v.insert(1, "x".to_owned());
+        assert!(v.spilled());
+        assert_eq!(&*v, ["a", "x", "b", "c", "d"]);
+
+        assert_eq!(v.remove(1), "x");
+        assert_eq!(v.swap_remove(0), "a");
+        assert_eq!(&*v, ["d", "b", "c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut v: SmallVector<u32, 4> = SmallVector::new();
+        v.insert(1, 0);
+    }
+
+    #[test]
+    fn drain() {
+        let mut v: SmallVector<String, 8> = (0..6).map(|i| i.to_string()).collect();
+        assert!(!v.spilled());
+
+        let drained: Vec<_> = v.drain(1..3).collect();
+        assert_eq!(drained, ["1", "2"]);
+        assert_eq!(&*v, ["0", "3", "4", "5"]);
+
+        let mut drain = v.drain(2..);
+        assert_eq!(drain.next(), Some("4".to_owned()));
+        drop(drain);
+        assert_eq!(&*v, ["0", "3"]);
+
+        let mut v: SmallVector<String, 2> = (0..6).map(|i| i.to_string()).collect();
+        assert!(v.spilled());
+
+        let drained: Vec<_> = v.drain(..4).collect();
+        assert_eq!(drained, ["0", "1", "2", "3"]);
+        assert_eq!(&*v, ["4", "5"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_past_end() {
+        let mut v: SmallVector<u32, 4> = SmallVector::new();
+        v.push(1);
+        v.drain(..2);
+    }
+
+    #[test]
+    fn zst() {
+        let mut v: SmallVector<(), 2> = SmallVector::new();
+        for _ in 0..10 {
+            v.push(());
+        }
+        // Zero-sized items take no space, so they never have to be moved to the heap
+        assert!(!v.spilled());
+        assert_eq!(v.capacity(), usize::MAX);
+        assert_eq!(v.len(), 10);
+        v.insert(3, ());
+        assert_eq!(v.remove(0), ());
+        assert_eq!(v.pop(), Some(()));
+        assert_eq!(v.drain(2..5).count(), 3);
+        assert_eq!(v.into_iter().count(), 6);
+    }
+}
@@ -0,0 +1,57 @@
+use super::{Data, SmallVector};
+
+impl<T, const N: usize> std::ops::Deref for SmallVector<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.data {
+            Data::Inline { buf, len } => unsafe {
+                std::slice::from_raw_parts(buf.as_ptr() as *const T, *len)
+            },
+            Data::Heap(vec) => vec,
+        }
+    }
+}
+
+impl<T, const N: usize> std::ops::DerefMut for SmallVector<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.data {
+            Data::Inline { buf, len } => unsafe {
+                std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut T, *len)
+            },
+            Data::Heap(vec) => vec,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deref() {
+        let mut v: SmallVector<u64, 2> = SmallVector::new();
+        v.push(3);
+        v.push(5);
+
+        let r: &[u64] = &v;
+        assert_eq!(r, [3, 5]);
+
+        v.push(8);
+        let r: &[u64] = &v;
+        assert_eq!(r, [3, 5, 8]);
+    }
+
+    #[test]
+    fn deref_mut() {
+        let mut v: SmallVector<u64, 2> = SmallVector::new();
+        v.push(5);
+        v[0] = 10;
+        assert_eq!(v[0], 10);
+
+        v.push(6);
+        v.push(7);
+        v[2] = 11;
+        assert_eq!(&*v, [10, 6, 11]);
+    }
+}
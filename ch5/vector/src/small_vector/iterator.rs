@@ -0,0 +1,131 @@
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::Range;
+
+use super::{Data, SmallVector};
+use crate::iterator;
+
+/// Safety:
+/// In the `Inline` variant, the items of `buf` in `range` are initialized and owned by the
+/// iterator
+pub struct IntoIter<T, const N: usize>(Inner<T, N>);
+
+enum Inner<T, const N: usize> {
+    Inline {
+        buf: [MaybeUninit<T>; N],
+        range: Range<usize>,
+    },
+    Heap(iterator::IntoIter<T>),
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    fn new(vec: SmallVector<T, N>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        // Safety:
+        // The `vec` is never dropped, so the data is moved out of it
+        let data = unsafe { std::ptr::read(&vec.data) };
+
+        let inner = match data {
+            Data::Inline { buf, len } => Inner::Inline { buf, range: 0..len },
+            Data::Heap(vec) => Inner::Heap(vec.into_iter()),
+        };
+
+        Self(inner)
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Inline { buf, range } => {
+                let idx = range.next()?;
+                Some(unsafe { (buf.as_ptr() as *const T).add(idx).read() })
+            }
+            Inner::Heap(iter) => iter.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Inner::Inline { range, .. } => range.size_hint(),
+            Inner::Heap(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVector<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        // Heap items are dropped by the `Vector` iterator
+        if let Inner::Inline { buf, range } = &mut self.0 {
+            let ptr = unsafe { (buf.as_mut_ptr() as *mut T).add(range.start) };
+            let slice = std::ptr::slice_from_raw_parts_mut(ptr, range.len());
+            unsafe { std::ptr::drop_in_place(slice) };
+        }
+    }
+}
+
+impl<A, const N: usize> FromIterator<A> for SmallVector<A, N> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A>,
+    {
+        let iter = iter.into_iter();
+        let (hint_low, hint_high) = iter.size_hint();
+        let hint = hint_high.unwrap_or(hint_low);
+        let mut this = Self::with_capacity(hint);
+
+        for elem in iter {
+            this.push(elem);
+        }
+
+        this
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn into_iter() {
+        let v: SmallVector<u32, 4> = (0..3).collect();
+        let items: Vec<_> = v.into_iter().collect();
+        assert_eq!(items, [0, 1, 2]);
+
+        let v: SmallVector<u32, 4> = (0..10).collect();
+        let mut iter = v.into_iter();
+        assert_eq!(iter.size_hint(), (10, Some(10)));
+        assert_eq!(iter.next(), Some(0));
+        let items: Vec<_> = iter.collect();
+        assert_eq!(items, (1..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn into_iter_drop() {
+        let item = Rc::new(());
+
+        let v: SmallVector<_, 4> = std::iter::repeat_n(item.clone(), 3).collect();
+        let mut iter = v.into_iter();
+        iter.next();
+        assert_eq!(Rc::strong_count(&item), 3);
+        drop(iter);
+        assert_eq!(Rc::strong_count(&item), 1);
+
+        let v: SmallVector<_, 4> = std::iter::repeat_n(item.clone(), 6).collect();
+        let mut iter = v.into_iter();
+        iter.next();
+        drop(iter);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
@@ -0,0 +1,153 @@
+//! The previous implementation of the map - linear probing with tombstones, growing at half the
+//! load - kept only for the comparison.
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+
+enum Cell<K, V> {
+    Empty,
+    Tombstone,
+    Item { hash: u64, key: K, value: V },
+}
+
+impl<K, V> Cell<K, V> {
+    fn is_empty(&self) -> bool {
+        !matches!(self, Self::Item { .. })
+    }
+}
+
+pub struct HashMap<K, V> {
+    vec: Vec<Cell<K, V>>,
+    used: usize,
+    hasher_builder: RandomState,
+}
+
+impl<K, V> HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            vec: Vec::new(),
+            used: 0,
+            hasher_builder: RandomState::new(),
+        }
+    }
+
+    fn cells_mut(&mut self, idx: usize) -> impl Iterator<Item = &'_ mut Cell<K, V>> {
+        let (post, pre) = self.vec.split_at_mut(idx);
+        pre.iter_mut().chain(post)
+    }
+
+    fn grow_to(&mut self, newcap: usize) {
+        let minsize = self.vec.len().max(16);
+        let newsize = std::iter::successors(Some(minsize), |size| Some(size * 2))
+            .find(|size| *size >= newcap * 2)
+            .unwrap();
+
+        if newsize > self.vec.len() {
+            self.vec.resize_with(newsize, || Cell::Empty);
+            self.rehash();
+        }
+    }
+
+    fn rehash(&mut self) {
+        let old = std::mem::take(&mut self.vec);
+        self.vec.resize_with(old.len(), || Cell::Empty);
+
+        for cell in old {
+            if let Cell::Item { hash, .. } = cell {
+                let idx = hash as usize % self.vec.len();
+                let slot = self.cells_mut(idx).find(|cell| cell.is_empty()).unwrap();
+                *slot = cell;
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.vec.is_empty() {
+            return None;
+        }
+
+        let h = self.hasher_builder.hash_one(k);
+        let idx = h as usize % self.vec.len();
+
+        self.vec[idx..]
+            .iter()
+            .chain(&self.vec[..idx])
+            .take_while(|cell| !matches!(cell, Cell::Empty))
+            .find_map(|cell| match cell {
+                Cell::Item { key, hash, value } if h == *hash && key.borrow() == k => Some(value),
+                _ => None,
+            })
+    }
+
+    pub fn insert(&mut self, k: K, mut v: V) -> Option<V> {
+        let h = self.hasher_builder.hash_one(&k);
+
+        if !self.vec.is_empty() {
+            let idx = h as usize % self.vec.len();
+
+            for cell in self.cells_mut(idx) {
+                match cell {
+                    Cell::Empty => break,
+                    Cell::Item { key, hash, value } if h == *hash && *key == k => {
+                        std::mem::swap(value, &mut v);
+                        return Some(v);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        self.grow_to(self.used + 1);
+
+        let idx = h as usize % self.vec.len();
+        let cell = self.cells_mut(idx).find(|cell| cell.is_empty()).unwrap();
+        *cell = Cell::Item {
+            key: k,
+            hash: h,
+            value: v,
+        };
+        self.used += 1;
+
+        None
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        if self.vec.is_empty() {
+            return None;
+        }
+
+        let h = self.hasher_builder.hash_one(k);
+        let idx = h as usize % self.vec.len();
+
+        let res = self
+            .cells_mut(idx)
+            .take_while(|cell| !matches!(cell, Cell::Empty))
+            .find_map(|cell| match cell {
+                Cell::Item { key, hash, .. } if h == *hash && (*key).borrow() == k => {
+                    let Cell::Item { value, .. } = std::mem::replace(cell, Cell::Tombstone) else {
+                        unreachable!()
+                    };
+                    Some(value)
+                }
+                _ => None,
+            });
+
+        if res.is_some() {
+            self.used -= 1;
+        }
+
+        res
+    }
+}
@@ -0,0 +1,133 @@
+//! Compares the Robin Hood map with the previous linear probing implementation and with
+//! `std::collections::HashMap`. All the maps use the `RandomState` hasher.
+//!
+//! Run with `cargo bench`.
+
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use hashmap::fnv::FnvBuildHasher;
+
+mod linear;
+
+const ITEMS: u64 = 100_000;
+const ROUNDS: usize = 10;
+
+/// Common interface of the compared maps
+trait Map: Default {
+    fn insert(&mut self, k: u64, v: u64) -> Option<u64>;
+    fn get(&self, k: &u64) -> Option<&u64>;
+    fn remove(&mut self, k: &u64) -> Option<u64>;
+}
+
+macro_rules! impl_map {
+    ($ty:ty) => {
+        impl Map for $ty {
+            fn insert(&mut self, k: u64, v: u64) -> Option<u64> {
+                self.insert(k, v)
+            }
+
+            fn get(&self, k: &u64) -> Option<&u64> {
+                self.get(k)
+            }
+
+            fn remove(&mut self, k: &u64) -> Option<u64> {
+                self.remove(k)
+            }
+        }
+    };
+}
+
+impl_map!(hashmap::HashMap<u64, u64>);
+impl_map!(std::collections::HashMap<u64, u64>);
+impl_map!(linear::HashMap<u64, u64>);
+
+impl Default for linear::HashMap<u64, u64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Scrambled keys, so they are not inserted in the hash order
+fn keys() -> Vec<u64> {
+    let hasher = FnvBuildHasher::default();
+    (0..ITEMS).map(|i| hasher.hash_one(i)).collect()
+}
+
+fn filled<M: Map>(keys: &[u64]) -> M {
+    let mut map = M::default();
+    for key in keys {
+        map.insert(*key, *key);
+    }
+    map
+}
+
+fn insert<M: Map>(keys: &[u64]) {
+    black_box(filled::<M>(keys));
+}
+
+fn get_hit<M: Map>(map: &M, keys: &[u64]) {
+    for key in keys {
+        black_box(map.get(key));
+    }
+}
+
+fn get_miss<M: Map>(map: &M, keys: &[u64]) {
+    for key in keys {
+        black_box(map.get(&!key));
+    }
+}
+
+/// Removes and reinserts every item - this is where the tombstones pile up
+fn churn<M: Map>(map: &mut M, keys: &[u64]) {
+    for (key, new) in keys.iter().zip(keys.iter().rev()) {
+        black_box(map.remove(key));
+        black_box(map.insert(!new, *new));
+    }
+    for (key, new) in keys.iter().zip(keys.iter().rev()) {
+        black_box(map.remove(&!new));
+        black_box(map.insert(*key, *key));
+    }
+}
+
+/// Returns the best time of all the rounds
+fn measure(mut f: impl FnMut()) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn bench<M: Map>(name: &str, keys: &[u64]) {
+    let insert = measure(|| insert::<M>(keys));
+
+    let map = filled::<M>(keys);
+    let hit = measure(|| get_hit(&map, keys));
+    let miss = measure(|| get_miss(&map, keys));
+
+    let mut map = filled::<M>(keys);
+    let churn = measure(|| churn(&mut map, keys));
+
+    println!(
+        "{name:<10} insert {:>10.2?}  get hit {:>10.2?}  get miss {:>10.2?}  churn {:>10.2?}",
+        insert, hit, miss, churn
+    );
+}
+
+fn main() {
+    // `cargo test --benches` runs this too - measure only on `cargo bench`, which passes `--bench`
+    if !std::env::args().any(|arg| arg == "--bench") {
+        return;
+    }
+
+    let keys = keys();
+    println!("{ITEMS} items, best of {ROUNDS} rounds");
+    bench::<hashmap::HashMap<u64, u64>>("robin hood", &keys);
+    bench::<linear::HashMap<u64, u64>>("linear", &keys);
+    bench::<std::collections::HashMap<u64, u64>>("std", &keys);
+}
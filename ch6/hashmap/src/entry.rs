@@ -1,10 +1,13 @@
-use crate::{Cell, HashMap};
+use crate::{insert_cell, remove_cell, Cell, HashMap};
+use std::borrow::Borrow;
 use std::hash::BuildHasher;
 use std::hash::Hash;
 
 pub struct VacantEntry<'map, K, V> {
-    cell: &'map mut Cell<K, V>,
+    table: &'map mut [Cell<K, V>],
     used: &'map mut usize,
+    /// Index where the item would be inserted
+    idx: usize,
     hash: u64,
     key: K,
 }
@@ -19,73 +22,102 @@ impl<'map, K, V> VacantEntry<'map, K, V> {
     }
 
     pub fn insert(self, value: V) -> &'map mut V {
+        self.insert_entry(value).into_mut()
+    }
+
+    /// Inserts the value, returning the entry of the inserted item
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'map, K, V> {
         *self.used += 1;
-        *self.cell = Cell::Item {
+        let cell = Cell::Item {
             key: self.key,
             hash: self.hash,
             value,
         };
+        insert_cell(self.table, self.idx, cell);
 
-        let Cell::Item { value, .. } = self.cell else {
-            unreachable!()
-        };
-
-        value
+        OccupiedEntry {
+            table: self.table,
+            used: self.used,
+            idx: self.idx,
+            key: None,
+        }
     }
 }
 
 pub struct OccupiedEntry<'map, K, V> {
-    cell: &'map mut Cell<K, V>,
+    table: &'map mut [Cell<K, V>],
     used: &'map mut usize,
+    idx: usize,
+    /// Key the entry was looked up with, if it was created by `HashMap::entry`
+    key: Option<K>,
 }
 
 impl<'map, K, V> OccupiedEntry<'map, K, V> {
     pub fn get(&self) -> &V {
-        let Cell::Item { value, .. } = &self.cell else {
+        let Cell::Item { value, .. } = &self.table[self.idx] else {
             unreachable!()
         };
         value
     }
 
     pub fn get_mut(&mut self) -> &mut V {
-        let Cell::Item { value, .. } = &mut self.cell else {
+        let Cell::Item { value, .. } = &mut self.table[self.idx] else {
             unreachable!()
         };
         value
     }
 
     pub fn into_mut(self) -> &'map mut V {
-        let Cell::Item { value, .. } = self.cell else {
+        self.into_key_value().1
+    }
+
+    /// Converts the entry into the references to the key and the value in the map
+    pub fn into_key_value(self) -> (&'map K, &'map mut V) {
+        let Cell::Item { key, value, .. } = &mut self.table[self.idx] else {
             unreachable!()
         };
-        value
+        (key, value)
     }
 
     pub fn insert(&mut self, mut value: V) -> V {
-        let Cell::Item { value: v, .. } = self.cell else {
-            unreachable!()
-        };
-        std::mem::swap(v, &mut value);
+        std::mem::swap(self.get_mut(), &mut value);
         value
     }
 
     pub fn key(&self) -> &K {
-        let Cell::Item { key, .. } = &self.cell else {
+        let Cell::Item { key, .. } = &self.table[self.idx] else {
+            unreachable!()
+        };
+        key
+    }
+
+    /// Replaces the key in the map with the one the entry was looked up with, returning the old
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// If the entry was not created by `HashMap::entry`, so there is no key to replace with.
+    pub fn replace_key(self) -> K {
+        let mut key = self.key.expect("Entry was not created with the owned key");
+        let Cell::Item { key: k, .. } = &mut self.table[self.idx] else {
             unreachable!()
         };
+        std::mem::swap(k, &mut key);
         key
     }
 
     pub fn remove(self) -> V {
-        *self.used -= 1;
-        let mut cell = Cell::Tombstone;
-        std::mem::swap(self.cell, &mut cell);
+        self.remove_entry().1
+    }
 
-        let Cell::Item { value, .. } = cell else {
+    /// Removes the item, returning both the key and the value
+    pub fn remove_entry(self) -> (K, V) {
+        *self.used -= 1;
+        let Cell::Item { key, value, .. } = remove_cell(self.table, self.idx) else {
             unreachable!()
         };
 
-        value
+        (key, value)
     }
 }
 
@@ -111,6 +143,17 @@ impl<'map, K, V> Entry<'map, K, V> {
         }
     }
 
+    /// Sets the value of the entry, returning the entry of the item
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'map, K, V> {
+        match self {
+            Entry::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            }
+            Entry::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+
     pub fn or_default(self) -> &'map mut V
     where
         V: Default,
@@ -120,10 +163,7 @@ impl<'map, K, V> Entry<'map, K, V> {
     }
 
     pub fn or_insert(self, default: V) -> &'map mut V {
-        match self {
-            Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(default),
-        }
+        self.or_insert_key_value(default).1
     }
 
     pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'map mut V {
@@ -134,9 +174,127 @@ impl<'map, K, V> Entry<'map, K, V> {
     }
 
     pub fn or_insert_with_key(self, default: impl FnOnce(&K) -> V) -> &'map mut V {
+        self.or_insert_with_key_value(default).1
+    }
+
+    /// Like `or_insert`, but returns the key in the map as well
+    pub fn or_insert_key_value(self, default: V) -> (&'map K, &'map mut V) {
         match self {
-            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Occupied(entry) => entry.into_key_value(),
+            Entry::Vacant(entry) => entry.insert_entry(default).into_key_value(),
+        }
+    }
+
+    /// Like `or_insert_with_key`, but returns the key in the map as well
+    pub fn or_insert_with_key_value(self, default: impl FnOnce(&K) -> V) -> (&'map K, &'map mut V) {
+        match self {
+            Entry::Occupied(entry) => entry.into_key_value(),
             Entry::Vacant(entry) => {
+                let default = default(entry.key());
+                entry.insert_entry(default).into_key_value()
+            }
+        }
+    }
+}
+
+/// Vacant entry looked up by the borrowed key, which is converted to the owned one only when
+/// inserting
+pub struct VacantEntryRef<'map, 'key, K, Q: ?Sized, V> {
+    table: &'map mut [Cell<K, V>],
+    used: &'map mut usize,
+    idx: usize,
+    hash: u64,
+    key: &'key Q,
+}
+
+impl<'map, 'key, K, Q, V> VacantEntryRef<'map, 'key, K, Q, V>
+where
+    Q: ToOwned<Owned = K> + ?Sized,
+{
+    pub fn key(&self) -> &'key Q {
+        self.key
+    }
+
+    /// Converts into the `VacantEntry`, creating the owned key
+    pub fn into_owned(self) -> VacantEntry<'map, K, V> {
+        VacantEntry {
+            table: self.table,
+            used: self.used,
+            idx: self.idx,
+            hash: self.hash,
+            key: self.key.to_owned(),
+        }
+    }
+
+    pub fn insert(self, value: V) -> &'map mut V {
+        self.into_owned().insert(value)
+    }
+
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'map, K, V> {
+        self.into_owned().insert_entry(value)
+    }
+}
+
+/// Entry looked up by the borrowed key. The occupied entry has no key to replace with.
+pub enum EntryRef<'map, 'key, K, Q: ?Sized, V> {
+    Occupied(OccupiedEntry<'map, K, V>),
+    Vacant(VacantEntryRef<'map, 'key, K, Q, V>),
+}
+
+impl<'map, 'key, K, Q, V> EntryRef<'map, 'key, K, Q, V>
+where
+    K: Borrow<Q>,
+    Q: ToOwned<Owned = K> + ?Sized,
+{
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        match &mut self {
+            EntryRef::Occupied(entry) => f(entry.get_mut()),
+            EntryRef::Vacant(_) => (),
+        }
+
+        self
+    }
+
+    pub fn key(&self) -> &Q {
+        match self {
+            EntryRef::Occupied(entry) => entry.key().borrow(),
+            EntryRef::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn insert_entry(self, value: V) -> OccupiedEntry<'map, K, V> {
+        match self {
+            EntryRef::Occupied(mut entry) => {
+                entry.insert(value);
+                entry
+            }
+            EntryRef::Vacant(entry) => entry.insert_entry(value),
+        }
+    }
+
+    pub fn or_default(self) -> &'map mut V
+    where
+        V: Default,
+    {
+        #[allow(clippy::unwrap_or_default)]
+        self.or_insert_with(V::default)
+    }
+
+    pub fn or_insert(self, default: V) -> &'map mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'map mut V {
+        match self {
+            EntryRef::Occupied(entry) => entry.into_mut(),
+            EntryRef::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key(self, default: impl FnOnce(&Q) -> V) -> &'map mut V {
+        match self {
+            EntryRef::Occupied(entry) => entry.into_mut(),
+            EntryRef::Vacant(entry) => {
                 let default = default(entry.key());
                 entry.insert(default)
             }
@@ -144,40 +302,71 @@ impl<'map, K, V> Entry<'map, K, V> {
     }
 }
 
-impl<K, V> HashMap<K, V>
+impl<K, V, S> HashMap<K, V, S>
 where
     K: Eq + Hash,
+    S: BuildHasher,
 {
-    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
-        if self.vec.is_empty() {
-            self.grow_to(1);
+    /// Finds the index of the key, or the one where it would be inserted. The table grows only if
+    /// the key is missing and it is full - before the final probe, as growing moves the items.
+    fn probe_entry<Q>(&mut self, hash: u64, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if !self.vec.is_empty() {
+            match self.probe(hash, key) {
+                Err(_) if self.used >= self.capacity() => (),
+                found => return found,
+            }
         }
 
+        self.grow_to(self.used + 1);
+        self.probe(hash, key)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
         let hash = self.hasher_builder.hash_one(&key);
-        let idx = hash as usize % self.vec.len();
-
-        for idx in self.idx_chain(idx) {
-            match &self.vec[idx] {
-                Cell::Item { key: k, .. } if *k == key => {
-                    return Entry::Occupied(OccupiedEntry {
-                        cell: &mut self.vec[idx],
-                        used: &mut self.used,
-                    })
-                }
-                Cell::Empty | Cell::Tombstone => {
-                    self.grow_to(self.used + 1);
-                    return Entry::Vacant(VacantEntry {
-                        cell: &mut self.vec[idx],
-                        used: &mut self.used,
-                        hash,
-                        key,
-                    });
-                }
-                _ => (),
-            }
+        match self.probe_entry(hash, &key) {
+            Ok(idx) => Entry::Occupied(OccupiedEntry {
+                table: &mut self.vec,
+                used: &mut self.used,
+                idx,
+                key: Some(key),
+            }),
+            Err(idx) => Entry::Vacant(VacantEntry {
+                table: &mut self.vec,
+                used: &mut self.used,
+                idx,
+                hash,
+                key,
+            }),
         }
+    }
 
-        unreachable!()
+    /// Like `entry`, but takes the borrowed key, which is converted to the owned one only if the
+    /// item is inserted
+    pub fn entry_ref<'key, Q>(&mut self, key: &'key Q) -> EntryRef<'_, 'key, K, Q, V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ToOwned<Owned = K> + ?Sized,
+    {
+        let hash = self.hasher_builder.hash_one(key);
+        match self.probe_entry(hash, key) {
+            Ok(idx) => EntryRef::Occupied(OccupiedEntry {
+                table: &mut self.vec,
+                used: &mut self.used,
+                idx,
+                key: None,
+            }),
+            Err(idx) => EntryRef::Vacant(VacantEntryRef {
+                table: &mut self.vec,
+                used: &mut self.used,
+                idx,
+                hash,
+                key,
+            }),
+        }
     }
 }
 
@@ -460,4 +649,136 @@ mod test {
         assert_eq!(map.get(&5), Some(&13));
         assert_eq!(map.get(&7), Some(&15));
     }
+
+    #[test]
+    fn remove_entry() {
+        let mut map: HashMap<String, u32> = HashMap::new();
+        map.insert("four".to_owned(), 4);
+        map.insert("five".to_owned(), 5);
+
+        let Entry::Occupied(entry) = map.entry("four".to_owned()) else {
+            panic!()
+        };
+
+        assert_eq!(entry.remove_entry(), ("four".to_owned(), 4));
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get("four"), None);
+    }
+
+    #[test]
+    fn replace_key() {
+        use std::rc::Rc;
+
+        let old: Rc<str> = Rc::from("key");
+        let new: Rc<str> = Rc::from("key");
+
+        let mut map = HashMap::new();
+        map.insert(old.clone(), 1);
+
+        let Entry::Occupied(entry) = map.entry(new.clone()) else {
+            panic!()
+        };
+        assert!(Rc::ptr_eq(&entry.replace_key(), &old));
+
+        let (key, value) = map.get_key_value("key").unwrap();
+        assert!(Rc::ptr_eq(key, &new));
+        assert_eq!(*value, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn replace_key_without_key() {
+        let mut map: HashMap<u32, u32> = HashMap::new();
+        let entry = map.entry(1).insert_entry(1);
+        entry.replace_key();
+    }
+
+    #[test]
+    fn insert_entry() {
+        let mut map: HashMap<u32, u32> = HashMap::new();
+        map.insert(4, 2);
+
+        let entry = map.entry(4).insert_entry(3);
+        assert_eq!(entry.key(), &4);
+        assert_eq!(entry.get(), &3);
+
+        let mut entry = map.entry(5).insert_entry(6);
+        assert_eq!(entry.key(), &5);
+        *entry.get_mut() += 1;
+
+        let mut items: Vec<_> = map.into_iter().collect();
+        items.sort();
+        assert_eq!(items, [(4, 3), (5, 7)]);
+    }
+
+    #[test]
+    fn or_insert_key_value() {
+        let mut map: HashMap<u32, u32> = HashMap::new();
+        map.insert(4, 2);
+
+        assert_eq!(map.entry(4).or_insert_key_value(10), (&4, &mut 2));
+        assert_eq!(map.entry(6).or_insert_key_value(11), (&6, &mut 11));
+
+        let (key, value) = map.entry(7).or_insert_with_key_value(|k| *k + 1);
+        assert_eq!(*key, 7);
+        *value += 1;
+        assert_eq!(
+            map.entry(7).or_insert_with_key_value(|_| unreachable!()),
+            (&7, &mut 9)
+        );
+    }
+
+    #[test]
+    fn entry_ref() {
+        let mut map: HashMap<String, usize> = HashMap::new();
+        for word in "the quick fox jumps over the lazy dog the end".split_whitespace() {
+            *map.entry_ref(word).or_insert(0) += 1;
+        }
+
+        assert_eq!(map.len(), 8);
+        assert_eq!(map["the"], 3);
+        assert_eq!(map["fox"], 1);
+
+        assert_eq!(map.entry_ref("the").key(), "the");
+        let EntryRef::Vacant(entry) = map.entry_ref("cat") else {
+            panic!()
+        };
+        assert_eq!(entry.key(), "cat");
+        assert_eq!(entry.into_owned().into_key(), "cat".to_owned());
+        assert!(!map.contains_key("cat"));
+
+        map.entry_ref("the").and_modify(|v| *v = 10);
+        assert_eq!(*map.entry_ref("cat").or_insert_with_key(|k| k.len()), 3);
+        assert_eq!(*map.entry_ref("dog").or_default(), 1);
+        assert_eq!(*map.entry_ref("bird").or_default(), 0);
+
+        let entry = map.entry_ref("the").insert_entry(5);
+        assert_eq!(entry.remove_entry(), ("the".to_owned(), 5));
+        assert_eq!(map.get("the"), None);
+    }
+
+    #[test]
+    fn grows_only_when_vacant() {
+        let mut map: HashMap<u32, u32> = HashMap::with_capacity(7);
+        for i in 0..7 {
+            map.entry(i).or_insert(i);
+        }
+        assert_eq!(map.len(), map.capacity());
+
+        let size = map.vec.len();
+        *map.entry(3).or_default() += 1;
+        map.entry_ref(&5).and_modify(|v| *v += 1);
+        assert_eq!(map.vec.len(), size);
+        assert_eq!(map[&3], 4);
+        assert_eq!(map[&5], 6);
+
+        map.entry(7).or_insert(7);
+        assert!(map.vec.len() > size);
+        assert!((0..8).all(|i| map.contains_key(&i)));
+
+        let mut map: HashMap<String, u32> = HashMap::new();
+        assert!(matches!(map.entry_ref("one"), EntryRef::Vacant(_)));
+        *map.entry_ref("one").or_default() += 1;
+        assert_eq!(map["one"], 1);
+    }
 }
@@ -0,0 +1,91 @@
+//! FNV-1a hasher - very simple and fast for short keys, but not resistant to HashDoS attacks, so
+//! it should be used only for trusted input
+
+use std::hash::{BuildHasherDefault, Hasher};
+
+const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const PRIME: u64 = 0x100000001b3;
+
+/// 64-bit FNV-1a hasher
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl FnvHasher {
+    /// Creates the hasher starting from the given state instead of the default offset basis
+    pub fn with_key(key: u64) -> Self {
+        Self(key)
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(OFFSET_BASIS)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Deterministic hasher builder creating `FnvHasher`s
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+/// `HashMap` using the FNV-1a hasher
+pub type FnvHashMap<K, V> = crate::HashMap<K, V, FnvBuildHasher>;
+
+/// `HashSet` using the FNV-1a hasher
+pub type FnvHashSet<T> = crate::HashSet<T, FnvBuildHasher>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn known_values() {
+        let hash = |bytes: &[u8]| {
+            let mut hasher = FnvHasher::default();
+            hasher.write(bytes);
+            hasher.finish()
+        };
+
+        assert_eq!(hash(b""), 0xcbf29ce484222325);
+        assert_eq!(hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(hash(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn deterministic() {
+        let first = FnvBuildHasher::default().hash_one("key");
+        let second = FnvBuildHasher::default().hash_one("key");
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn map() {
+        let mut map: FnvHashMap<u64, u64> = FnvHashMap::default();
+        for i in 0..1000 {
+            map.insert(i, i * 2);
+        }
+
+        for i in 0..1000 {
+            assert_eq!(map.get(&i), Some(&(i * 2)));
+        }
+        assert_eq!(map.remove(&10), Some(20));
+        assert_eq!(map.get(&10), None);
+
+        let mut set: FnvHashSet<&str> = FnvHashSet::with_capacity_and_hasher(4, Default::default());
+        set.insert("one");
+        assert_eq!(set.get("one"), Some(&"one"));
+        assert_eq!(set.get("two"), None);
+    }
+}
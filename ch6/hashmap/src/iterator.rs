@@ -1,13 +1,70 @@
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash};
 use std::{slice, vec};
 
 use crate::{Cell, HashMap};
 
-pub struct IntoIter<K, V>(vec::IntoIter<Cell<K, V>>);
+impl<K, V, S> HashMap<K, V, S> {
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut::new(self)
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys(self.iter())
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values(self.iter())
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut(self.iter_mut())
+    }
+
+    pub fn into_keys(self) -> IntoKeys<K, V> {
+        IntoKeys(self.into_iter())
+    }
+
+    pub fn into_values(self) -> IntoValues<K, V> {
+        IntoValues(self.into_iter())
+    }
+
+    /// Removes all the items, returning them in an iterator. The allocated memory is kept.
+    ///
+    /// The items not consumed by the iterator are dropped with it.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            cells: std::mem::replace(&mut self.vec, Self::table(0)),
+            idx: 0,
+            remaining: std::mem::replace(&mut self.used, 0),
+            table: &mut self.vec,
+        }
+    }
+}
+
+/// Takes the key and value out of an occupied cell
+fn into_item<K, V>(cell: Cell<K, V>) -> Option<(K, V)> {
+    match cell {
+        Cell::Item { key, value, .. } => Some((key, value)),
+        Cell::Empty => None,
+    }
+}
+
+pub struct IntoIter<K, V> {
+    cells: vec::IntoIter<Cell<K, V>>,
+    /// Number of items not returned yet, so the iterator knows its exact length
+    remaining: usize,
+}
 
 impl<K, V> IntoIter<K, V> {
-    fn new(map: HashMap<K, V>) -> Self {
-        Self(map.vec.into_iter())
+    fn new<S>(map: HashMap<K, V, S>) -> Self {
+        Self {
+            cells: map.vec.into_iter(),
+            remaining: map.used,
+        }
     }
 }
 
@@ -15,18 +72,19 @@ impl<K, V> Iterator for IntoIter<K, V> {
     type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        #[allow(clippy::while_let_on_iterator)]
-        while let Some(cell) = self.0.next() {
-            if let Cell::Item { key, value, .. } = cell {
-                return Some((key, value));
-            }
-        }
+        let item = self.cells.find_map(into_item)?;
+        self.remaining -= 1;
+        Some(item)
+    }
 
-        None
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<K, V> IntoIterator for HashMap<K, V> {
+impl<K, V> ExactSizeIterator for IntoIter<K, V> {}
+
+impl<K, V, S> IntoIterator for HashMap<K, V, S> {
     type Item = (K, V);
     type IntoIter = IntoIter<K, V>;
 
@@ -35,11 +93,17 @@ impl<K, V> IntoIterator for HashMap<K, V> {
     }
 }
 
-pub struct Iter<'map, K, V>(slice::Iter<'map, Cell<K, V>>);
+pub struct Iter<'map, K, V> {
+    cells: slice::Iter<'map, Cell<K, V>>,
+    remaining: usize,
+}
 
 impl<'map, K, V> Iter<'map, K, V> {
-    fn new(map: &'map HashMap<K, V>) -> Self {
-        Self(map.vec.iter())
+    fn new<S>(map: &'map HashMap<K, V, S>) -> Self {
+        Self {
+            cells: map.vec.iter(),
+            remaining: map.used,
+        }
     }
 }
 
@@ -47,18 +111,22 @@ impl<'map, K, V> Iterator for Iter<'map, K, V> {
     type Item = (&'map K, &'map V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        #[allow(clippy::while_let_on_iterator)]
-        while let Some(cell) = self.0.next() {
-            if let Cell::Item { key, value, .. } = cell {
-                return Some((key, value));
-            }
-        }
+        let item = self.cells.find_map(|cell| match cell {
+            Cell::Item { key, value, .. } => Some((key, value)),
+            Cell::Empty => None,
+        })?;
+        self.remaining -= 1;
+        Some(item)
+    }
 
-        None
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<'map, K, V> IntoIterator for &'map HashMap<K, V> {
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+impl<'map, K, V, S> IntoIterator for &'map HashMap<K, V, S> {
     type Item = (&'map K, &'map V);
     type IntoIter = Iter<'map, K, V>;
 
@@ -67,11 +135,17 @@ impl<'map, K, V> IntoIterator for &'map HashMap<K, V> {
     }
 }
 
-pub struct IterMut<'map, K, V>(slice::IterMut<'map, Cell<K, V>>);
+pub struct IterMut<'map, K, V> {
+    cells: slice::IterMut<'map, Cell<K, V>>,
+    remaining: usize,
+}
 
 impl<'map, K, V> IterMut<'map, K, V> {
-    fn new(map: &'map mut HashMap<K, V>) -> Self {
-        Self(map.vec.iter_mut())
+    fn new<S>(map: &'map mut HashMap<K, V, S>) -> Self {
+        Self {
+            cells: map.vec.iter_mut(),
+            remaining: map.used,
+        }
     }
 }
 
@@ -79,18 +153,22 @@ impl<'map, K, V> Iterator for IterMut<'map, K, V> {
     type Item = (&'map K, &'map mut V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        #[allow(clippy::while_let_on_iterator)]
-        while let Some(cell) = self.0.next() {
-            if let Cell::Item { key, value, .. } = cell {
-                return Some((key, value));
-            }
-        }
+        let item = self.cells.find_map(|cell| match cell {
+            Cell::Item { key, value, .. } => Some((&*key, value)),
+            Cell::Empty => None,
+        })?;
+        self.remaining -= 1;
+        Some(item)
+    }
 
-        None
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
-impl<'map, K, V> IntoIterator for &'map mut HashMap<K, V> {
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+impl<'map, K, V, S> IntoIterator for &'map mut HashMap<K, V, S> {
     type Item = (&'map K, &'map mut V);
     type IntoIter = IterMut<'map, K, V>;
 
@@ -99,15 +177,132 @@ impl<'map, K, V> IntoIterator for &'map mut HashMap<K, V> {
     }
 }
 
-impl<K, V> FromIterator<(K, V)> for HashMap<K, V>
+pub struct Keys<'map, K, V>(Iter<'map, K, V>);
+
+impl<'map, K, V> Iterator for Keys<'map, K, V> {
+    type Item = &'map K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
+
+pub struct Values<'map, K, V>(Iter<'map, K, V>);
+
+impl<'map, K, V> Iterator for Values<'map, K, V> {
+    type Item = &'map V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
+
+pub struct ValuesMut<'map, K, V>(IterMut<'map, K, V>);
+
+impl<'map, K, V> Iterator for ValuesMut<'map, K, V> {
+    type Item = &'map mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+
+pub struct IntoKeys<K, V>(IntoIter<K, V>);
+
+impl<K, V> Iterator for IntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, _)| key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoKeys<K, V> {}
+
+pub struct IntoValues<K, V>(IntoIter<K, V>);
+
+impl<K, V> Iterator for IntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(_, value)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<K, V> ExactSizeIterator for IntoValues<K, V> {}
+
+pub struct Drain<'map, K, V> {
+    /// The table taken out of the map, so the map stays consistent (empty) even if the `Drain` is
+    /// leaked, or a destructor panics
+    cells: Vec<Cell<K, V>>,
+    /// Index of the next cell to visit
+    idx: usize,
+    remaining: usize,
+    /// Where the emptied table is returned to, so the allocated memory is kept
+    table: &'map mut Vec<Cell<K, V>>,
+}
+
+impl<'map, K, V> Iterator for Drain<'map, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.cells[self.idx..]
+            .iter()
+            .position(|cell| !cell.is_empty())?;
+        let cell = std::mem::replace(&mut self.cells[self.idx + offset], Cell::Empty);
+        self.idx += offset + 1;
+        self.remaining -= 1;
+        into_item(cell)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+impl<'map, K, V> Drop for Drain<'map, K, V> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+        *self.table = std::mem::take(&mut self.cells);
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for HashMap<K, V, S>
 where
     K: Eq + Hash,
+    S: BuildHasher + Default,
 {
     fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
         let iter = iter.into_iter();
-        let (hint_low, hint_high) = iter.size_hint();
-        let hint = hint_high.unwrap_or(hint_low);
-        let mut this = Self::with_capacity(hint);
+        let mut this = Self::with_capacity_and_hasher(iter.size_hint().0, S::default());
 
         for (key, value) in iter {
             this.insert(key, value);
@@ -117,6 +312,32 @@ where
     }
 }
 
+impl<K, V, S> Extend<(K, V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K, V, S> Extend<(&'a K, &'a V)> for HashMap<K, V, S>
+where
+    K: Eq + Hash + Copy,
+    V: Copy,
+    S: BuildHasher,
+{
+    fn extend<T: IntoIterator<Item = (&'a K, &'a V)>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|(key, value)| (*key, *value)))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -182,4 +403,142 @@ mod test {
         items.sort();
         assert_eq!(items, [(2, 10), (10, 22), (15, 12)]);
     }
+
+    #[test]
+    fn keys_and_values() {
+        let mut map: HashMap<u64, u64> = (0..10).map(|i| (i, i * 2)).collect();
+
+        let mut keys: Vec<_> = map.keys().copied().collect();
+        keys.sort();
+        assert_eq!(keys, (0..10).collect::<Vec<_>>());
+
+        for value in map.values_mut() {
+            *value += 1;
+        }
+
+        let mut values: Vec<_> = map.values().copied().collect();
+        values.sort();
+        assert_eq!(values, (0..10).map(|i| i * 2 + 1).collect::<Vec<_>>());
+
+        let mut items: Vec<_> = map.iter_mut().map(|(k, v)| (*k, *v)).collect();
+        items.sort();
+        assert_eq!(items, (0..10).map(|i| (i, i * 2 + 1)).collect::<Vec<_>>());
+
+        let mut values: Vec<_> = map.clone().into_values().collect();
+        values.sort();
+        assert_eq!(values, (0..10).map(|i| i * 2 + 1).collect::<Vec<_>>());
+
+        let mut keys: Vec<_> = map.into_keys().collect();
+        keys.sort();
+        assert_eq!(keys, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn drain() {
+        let mut map: HashMap<u64, String> = (0..10).map(|i| (i, i.to_string())).collect();
+        let cap = map.capacity();
+
+        let mut items: Vec<_> = map.drain().collect();
+        items.sort();
+        assert_eq!(
+            items,
+            (0..10).map(|i| (i, i.to_string())).collect::<Vec<_>>()
+        );
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), cap);
+        assert_eq!(map.get(&1), None);
+
+        map.extend((0..10).map(|i| (i, i.to_string())));
+        assert_eq!(map.drain().take(3).count(), 3);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+
+        map.extend((0..10).map(|i| (i, i.to_string())));
+        let mut drain = map.drain();
+        drain.next();
+        std::mem::forget(drain);
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+
+        map.extend((0..10).map(|i| (i, i.to_string())));
+        assert_eq!(map.get(&3), Some(&"3".to_owned()));
+    }
+
+    #[test]
+    fn drain_leaked_with_collisions() {
+        #[derive(Default)]
+        struct ConstHasher;
+
+        impl std::hash::Hasher for ConstHasher {
+            fn write(&mut self, _bytes: &[u8]) {}
+
+            fn finish(&self) -> u64 {
+                0
+            }
+        }
+
+        let mut map: HashMap<u64, u64, std::hash::BuildHasherDefault<ConstHasher>> =
+            (0..6).map(|i| (i, i)).collect();
+        let mut drain = map.drain();
+        drain.next();
+        std::mem::forget(drain);
+
+        assert_eq!(map.len(), map.iter().count());
+        assert!(map.keys().all(|key| map.contains_key(key)));
+
+        map.extend((0..6).map(|i| (i, i * 2)));
+        assert!((0..6).all(|i| map.get(&i) == Some(&(i * 2))));
+    }
+
+    #[test]
+    fn extend() {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        map.extend([(1, 1), (2, 2)]);
+        map.extend([(&2, &3), (&3, &4)]);
+
+        let mut items: Vec<_> = map.into_iter().collect();
+        items.sort();
+        assert_eq!(items, [(1, 1), (2, 3), (3, 4)]);
+    }
+
+    #[test]
+    fn exact_size() {
+        let mut map: HashMap<u64, u64> = (0..10).map(|i| (i, i * 2)).collect();
+        map.remove(&3);
+
+        let mut iter = map.iter();
+        assert_eq!(iter.len(), 9);
+        iter.next();
+        assert_eq!(iter.size_hint(), (8, Some(8)));
+        assert_eq!(iter.count(), 8);
+
+        assert_eq!(map.keys().len(), 9);
+        assert_eq!(map.values().len(), 9);
+        assert_eq!(map.values_mut().len(), 9);
+        assert_eq!(map.iter_mut().skip(4).len(), 5);
+        assert_eq!(map.clone().into_keys().len(), 9);
+        assert_eq!(map.clone().into_values().len(), 9);
+
+        let mut iter = map.clone().into_iter();
+        iter.next();
+        assert_eq!(iter.len(), 8);
+
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), 9);
+        drain.next();
+        assert_eq!(drain.len(), 8);
+        drop(drain);
+
+        assert_eq!(HashMap::<u64, u64>::new().iter().len(), 0);
+    }
+
+    #[test]
+    fn from_iterator_hint() {
+        // Only the lower bound is used for the capacity - the upper one may be huge
+        let items = (0..usize::MAX).take_while(|i| *i < 3).map(|i| (i, i));
+        assert_eq!(items.size_hint(), (0, Some(usize::MAX)));
+
+        let map: HashMap<usize, usize> = items.collect();
+        assert_eq!(map.len(), 3);
+    }
 }
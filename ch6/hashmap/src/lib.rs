@@ -1,303 +1,396 @@
 use std::borrow::Borrow;
 use std::collections::hash_map::RandomState;
+use std::fmt;
 use std::hash::{BuildHasher, Hash};
+use std::ops::Index;
 
 mod entry;
+pub mod fnv;
 mod iterator;
+pub mod set;
+
+pub use entry::{Entry, EntryRef, OccupiedEntry, VacantEntry, VacantEntryRef};
+pub use iterator::{Drain, IntoIter, IntoKeys, IntoValues, Iter, IterMut, Keys, Values, ValuesMut};
+pub use set::HashSet;
 
 pub const MIN_SIZE_SHIFT: usize = 3;
 
-#[derive(Debug)]
+/// Maximum fraction of the cells which can be occupied, before the map grows
+const MAX_LOAD: (usize, usize) = (7, 8);
+
+#[derive(Debug, Clone)]
 enum Cell<K, V> {
     Empty,
-    Tombstone,
     Item { hash: u64, key: K, value: V },
 }
 
 impl<K, V> Cell<K, V> {
-    /// `Empty` or `Tombstone` - unoccupied entry
     fn is_empty(&self) -> bool {
-        match self {
-            Self::Empty | Self::Tombstone => true,
-            Self::Item { .. } => false,
+        matches!(self, Self::Empty)
+    }
+}
+
+/// How far is the item with the `hash` stored on `idx` from its desired index
+fn distance(hash: u64, idx: usize, mask: usize) -> usize {
+    idx.wrapping_sub(hash as usize) & mask
+}
+
+/// Finds the index where the item with the `hash` should be inserted - the first empty cell or
+/// the first one with an item closer to its desired index, than the new item would be.
+///
+/// The table has to contain at least one empty cell.
+fn vacant_idx<K, V>(table: &[Cell<K, V>], h: u64) -> usize {
+    let mask = table.len() - 1;
+    let mut idx = h as usize & mask;
+
+    for dist in 0.. {
+        match &table[idx] {
+            Cell::Item { hash, .. } if distance(*hash, idx, mask) >= dist => (),
+            _ => break,
+        }
+        idx = (idx + 1) & mask;
+    }
+
+    idx
+}
+
+/// Puts the `cell` on the `idx`, moving all the following items up to the first empty cell one
+/// cell further. This keeps the items in every chain ordered by their desired index.
+///
+/// The table has to contain at least one empty cell.
+fn insert_cell<K, V>(table: &mut [Cell<K, V>], idx: usize, mut cell: Cell<K, V>) {
+    let mask = table.len() - 1;
+    let mut idx = idx;
+
+    while !cell.is_empty() {
+        cell = std::mem::replace(&mut table[idx], cell);
+        idx = (idx + 1) & mask;
+    }
+}
+
+/// Takes the cell from the `idx`, moving the following items of the chain one cell back (the
+/// backward shift deletion), so no tombstones are needed.
+fn remove_cell<K, V>(table: &mut [Cell<K, V>], idx: usize) -> Cell<K, V> {
+    let mask = table.len() - 1;
+    let cell = std::mem::replace(&mut table[idx], Cell::Empty);
+    let mut idx = idx;
+
+    loop {
+        let next = (idx + 1) & mask;
+        match &table[next] {
+            Cell::Item { hash, .. } if distance(*hash, next, mask) > 0 => {
+                table.swap(idx, next);
+                idx = next;
+            }
+            _ => return cell,
         }
     }
 }
 
-pub struct HashMap<K, V> {
+/// Hash map using Robin Hood hashing - linear probing, where the items in every chain are kept
+/// ordered by their desired index. That bounds the probe sequences even for high load factors,
+/// and allows removing items without tombstones.
+#[derive(Clone)]
+pub struct HashMap<K, V, S = RandomState> {
+    /// Always empty, or of power of two size
     vec: Vec<Cell<K, V>>,
-    /// How many items are used - when it reaches half an alloc_size, the memory is reallocated
+    /// How many items are used - when it would exceed the `MAX_LOAD` of the `vec`, the memory is
+    /// reallocated
     used: usize,
-    hasher_builder: RandomState,
+    hasher_builder: S,
 }
 
 impl<K, V> HashMap<K, V> {
     pub fn new() -> Self {
+        Self::with_hasher(RandomState::default())
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_and_hasher(cap, RandomState::default())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Creates an empty map, using `hasher_builder` to hash the keys
+    pub fn with_hasher(hasher_builder: S) -> Self {
         Self {
             vec: Vec::new(),
             used: 0,
-            hasher_builder: RandomState::default(),
+            hasher_builder,
         }
     }
 
-    pub fn with_capacity(cap: usize) -> Self {
+    pub fn with_capacity_and_hasher(cap: usize, hasher_builder: S) -> Self {
         Self {
-            vec: std::iter::repeat_with(|| Cell::Empty)
-                .take(Self::next_size(cap))
-                .collect(),
+            vec: Self::table(Self::next_size(cap)),
             used: 0,
-            hasher_builder: RandomState::default(),
+            hasher_builder,
         }
     }
 
-    /// Returns proper map size for at least given capacity
-    fn next_size(cap: usize) -> usize {
-        let newsize = cap * 2;
-        (MIN_SIZE_SHIFT..).find(|i| (2 << *i) >= newsize).unwrap()
+    /// Returns the hasher builder used by the map
+    pub fn hasher(&self) -> &S {
+        &self.hasher_builder
     }
 
-    /// Indicies chain starting from given index, wrapping around the map, visiting every index
-    /// once
-    fn idx_chain(&self, idx: usize) -> impl Iterator<Item = usize> {
-        (idx..self.vec.len()).chain(0..idx)
+    pub fn len(&self) -> usize {
+        self.used
     }
 
-    fn cells(&self, idx: usize) -> impl Iterator<Item = &'_ Cell<K, V>> {
-        let pre = &self.vec[idx..];
-        let post = &self.vec[..idx];
-        pre.iter().chain(post)
+    pub fn is_empty(&self) -> bool {
+        self.used == 0
     }
 
-    fn cells_mut(&mut self, idx: usize) -> impl Iterator<Item = &'_ mut Cell<K, V>> {
-        let (post, pre) = self.vec.split_at_mut(idx);
-        pre.iter_mut().chain(post)
+    /// Number of items the map can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        Self::capacity_of(self.vec.len())
     }
-}
 
-impl<K, V> HashMap<K, V>
-where
-    K: Eq + Hash,
-{
-    /// After this call, the map should have capacity to fit at least `newcap`.
-    fn grow_to(&mut self, newcap: usize) {
-        let minsize = self.vec.len().max(2 << MIN_SIZE_SHIFT);
-        let newsize = std::iter::successors(Some(minsize), |size| Some(size * 2))
-            .find(|size| *size >= newcap * 2)
-            .unwrap();
-
-        if newsize > self.vec.len() {
-            self.vec.resize_with(newsize, || Cell::Empty);
-            self.rehash();
+    /// Removes all the items, keeping the allocated memory
+    pub fn clear(&mut self) {
+        for cell in &mut self.vec {
+            *cell = Cell::Empty;
         }
+        self.used = 0;
     }
 
-    pub fn rehash(&mut self) {
-        for i in 0..self.vec.len() {
-            let hash = match &self.vec[i] {
-                Cell::Tombstone => {
-                    self.vec[i] = Cell::Empty;
-                    continue;
+    /// Keeps only the items for which `f` returns `true`, visiting every item once
+    pub fn retain(&mut self, mut f: impl FnMut(&K, &mut V) -> bool) {
+        // Starting right after an empty cell, so no chain wraps around to the visited cells
+        let Some(mut idx) = self.vec.iter().position(Cell::is_empty) else {
+            return;
+        };
+        let mask = self.vec.len() - 1;
+
+        for _ in 1..self.vec.len() {
+            idx = (idx + 1) & mask;
+            // Removing shifts the next item of the chain to `idx`, so it has to be visited
+            while let Cell::Item { key, value, .. } = &mut self.vec[idx] {
+                if f(key, value) {
+                    break;
                 }
-                Cell::Empty => continue,
-                Cell::Item { hash, .. } => *hash,
-            };
-
-            let idx = hash as usize % self.vec.len();
-            if idx == i {
-                continue;
+                remove_cell(&mut self.vec, idx);
+                self.used -= 1;
             }
+        }
+    }
 
-            // Find first unoccuppied index. It is guaranteed to find one, as we only fill half the
-            // map at most
-            let idx = self
-                .idx_chain(idx)
-                .find(|idx| self.vec[*idx].is_empty())
-                .unwrap();
-
-            self.vec[idx] = Cell::Empty;
-            self.vec.swap(i, idx);
+    /// Returns proper map size for at least given capacity
+    fn next_size(cap: usize) -> usize {
+        if cap == 0 {
+            return 0;
         }
+
+        (MIN_SIZE_SHIFT..usize::BITS as usize)
+            .map(|shift| 1 << shift)
+            .find(|size| Self::capacity_of(*size) >= cap)
+            .expect("capacity overflow")
     }
 
-    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    /// How many items fit in the map of given size
+    fn capacity_of(size: usize) -> usize {
+        size / MAX_LOAD.1 * MAX_LOAD.0
+    }
+
+    fn table(size: usize) -> Vec<Cell<K, V>> {
+        std::iter::repeat_with(|| Cell::Empty).take(size).collect()
+    }
+
+    /// Looks for the key in the non-empty map, returning its index if found, or the index where
+    /// it should be inserted otherwise
+    fn probe<Q>(&self, h: u64, k: &Q) -> Result<usize, usize>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Eq + ?Sized,
     {
-        if self.vec.is_empty() {
-            return None;
+        let mask = self.vec.len() - 1;
+        let mut idx = h as usize & mask;
+
+        for dist in 0.. {
+            match &self.vec[idx] {
+                Cell::Item { hash, key, .. } if distance(*hash, idx, mask) >= dist => {
+                    if h == *hash && key.borrow() == k {
+                        return Ok(idx);
+                    }
+                }
+                // Either empty, or all the items of this hash would be before this one
+                _ => break,
+            }
+            idx = (idx + 1) & mask;
         }
 
-        let h = self.hasher_builder.hash_one(k);
-        let idx = h as usize % self.vec.len();
-
-        self.cells(idx)
-            .take_while(|cell| !matches!(cell, Cell::Empty))
-            .find_map(|cell| match cell {
-                Cell::Item { key, hash, value } if h == *hash && key.borrow() == k => Some(value),
-                _ => None,
-            })
+        Err(idx)
     }
 
-    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    fn find<Q>(&self, h: u64, k: &Q) -> Option<usize>
     where
         K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Eq + ?Sized,
     {
         if self.vec.is_empty() {
             return None;
         }
 
-        let h = self.hasher_builder.hash_one(k);
-        let idx = h as usize % self.vec.len();
-
-        self.cells_mut(idx)
-            .take_while(|cell| !matches!(cell, Cell::Empty))
-            .find_map(|cell| match cell {
-                Cell::Item { key, hash, value } if h == *hash && Borrow::<Q>::borrow(key) == k => {
-                    Some(value)
-                }
-                _ => None,
-            })
+        self.probe(h, k).ok()
     }
+}
 
-    pub fn insert(&mut self, k: K, mut v: V) -> Option<V> {
-        let h = self.hasher_builder.hash_one(&k);
-
-        if !self.vec.is_empty() {
-            let idx = h as usize % self.vec.len();
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    /// After this call, the map should have capacity to fit at least `newcap`.
+    fn grow_to(&mut self, newcap: usize) {
+        if newcap > self.capacity() {
+            self.resize(Self::next_size(newcap));
+        }
+    }
 
-            for cell in self.cells_mut(idx) {
-                match cell {
-                    Cell::Empty => break,
-                    Cell::Item { key, hash, value } if h == *hash && *key == k => {
-                        std::mem::swap(value, &mut v);
-                        return Some(v);
-                    }
-                    _ => (),
-                }
+    /// Moves all the items to the new table of the given size
+    fn resize(&mut self, size: usize) {
+        let old = std::mem::replace(&mut self.vec, Self::table(size));
+        for cell in old {
+            if let Cell::Item { hash, .. } = cell {
+                let idx = vacant_idx(&self.vec, hash);
+                insert_cell(&mut self.vec, idx, cell);
             }
         }
+    }
 
-        self.grow_to(self.used + 1);
+    /// Reserves capacity for at least `additional` more items
+    pub fn reserve(&mut self, additional: usize) {
+        self.grow_to(
+            self.used
+                .checked_add(additional)
+                .expect("capacity overflow"),
+        );
+    }
 
-        let idx = h as usize % self.vec.len();
+    /// Shrinks the memory to the smallest size fitting all the items
+    pub fn shrink_to_fit(&mut self) {
+        let size = Self::next_size(self.used);
+        if size < self.vec.len() {
+            self.resize(size);
+        }
+    }
 
-        let cell = self
-            .cells_mut(idx)
-            .find_map(|cell| if cell.is_empty() { Some(cell) } else { None })
-            .unwrap();
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(k).map(|(_, value)| value)
+    }
 
-        *cell = Cell::Item {
-            key: k,
-            hash: h,
-            value: v,
+    pub fn get_key_value<Q>(&self, k: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(self.hasher_builder.hash_one(k), k)?;
+        let Cell::Item { key, value, .. } = &self.vec[idx] else {
+            unreachable!()
         };
+        Some((key, value))
+    }
 
-        self.used += 1;
-
-        None
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hasher_builder.hash_one(k), k).is_some()
     }
 
-    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
     where
         K: Borrow<Q>,
         Q: Hash + Eq + ?Sized,
     {
-        let h = self.hasher_builder.hash_one(k);
-        let idx = h as usize % self.vec.len();
-
-        let res = self
-            .cells_mut(idx)
-            .take_while(|cell| !matches!(cell, Cell::Empty))
-            .find_map(|cell| match cell {
-                Cell::Item { key, hash, .. } if h == *hash && (*key).borrow() == k => {
-                    let mut result = Cell::Tombstone;
-                    std::mem::swap(cell, &mut result);
-                    let Cell::Item { value, .. } = result else {
-                        unreachable!()
-                    };
-
-                    Some(value)
-                }
-                _ => None,
-            });
+        let idx = self.find(self.hasher_builder.hash_one(k), k)?;
+        let Cell::Item { value, .. } = &mut self.vec[idx] else {
+            unreachable!()
+        };
+        Some(value)
+    }
 
-        if res.is_some() {
-            self.used -= 1;
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        match self.entry(k) {
+            Entry::Occupied(mut entry) => Some(entry.insert(v)),
+            Entry::Vacant(entry) => {
+                entry.insert(v);
+                None
+            }
         }
+    }
+
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let idx = self.find(self.hasher_builder.hash_one(k), k)?;
+        let Cell::Item { value, .. } = remove_cell(&mut self.vec, idx) else {
+            unreachable!()
+        };
+        self.used -= 1;
 
-        res
+        Some(value)
     }
 }
 
-impl<K, V> Default for HashMap<K, V> {
+impl<K, V, S> Default for HashMap<K, V, S>
+where
+    S: Default,
+{
     fn default() -> Self {
-        Self::new()
+        Self::with_hasher(S::default())
     }
 }
 
-pub struct HashSet<T>(HashMap<T, ()>);
-
-impl<T> HashSet<T> {
-    pub fn new() -> Self {
-        Self(HashMap::new())
-    }
-
-    pub fn with_capacity(cap: usize) -> Self {
-        Self(HashMap::with_capacity(cap))
+impl<K, V, S> fmt::Debug for HashMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
     }
 }
 
-impl<T> HashSet<T>
+impl<K, V, S> PartialEq for HashMap<K, V, S>
 where
-    T: Eq + Hash,
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
 {
-    pub fn rehash(&mut self) {
-        self.0.rehash()
-    }
-
-    pub fn get<Q>(&self, value: &Q) -> Option<&T>
-    where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
-    {
-        let map = &self.0;
-
-        if map.vec.is_empty() {
-            return None;
-        }
-
-        let h = map.hasher_builder.hash_one(value);
-        let idx = h as usize % map.vec.len();
-
-        map.idx_chain(idx)
-            .map_while(|idx| match &map.vec[idx] {
-                Cell::Empty => None,
-                item => Some(item),
-            })
-            .find_map(|item| match item {
-                Cell::Item { key, hash, .. } if h == *hash && key.borrow() == value => Some(key),
-                _ => None,
-            })
-    }
-
-    pub fn insert(&mut self, value: T) -> bool {
-        self.0.insert(value, ()).is_some()
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
     }
+}
 
-    pub fn remove<Q>(&mut self, k: &Q) -> bool
-    where
-        T: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
-    {
-        self.0.remove(k).is_some()
-    }
+impl<K, V, S> Eq for HashMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq,
+    S: BuildHasher,
+{
 }
 
-impl<T> Default for HashSet<T> {
-    fn default() -> Self {
-        Self::new()
+impl<K, Q, V, S> Index<&Q> for HashMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: Eq + Hash + ?Sized,
+    S: BuildHasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("Key not found")
     }
 }
 
@@ -403,4 +496,318 @@ mod test {
         let items: Vec<_> = map.into_iter().collect();
         assert_eq!(items, [(2, 10)]);
     }
+
+    #[test]
+    fn remove_from_empty() {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        assert_eq!(map.remove(&1), None);
+
+        let mut map: HashMap<String, u64> = HashMap::with_capacity(0);
+        assert_eq!(map.remove("one"), None);
+
+        let mut set: HashSet<u64> = HashSet::new();
+        assert!(!set.remove(&1));
+    }
+
+    #[test]
+    fn churn() {
+        // Every key is inserted and removed once, so without cleaning up after removals all the
+        // cells would eventually be used up
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        for i in 0..10000 {
+            assert_eq!(map.insert(i, i), None);
+            if i >= 4 {
+                assert_eq!(map.remove(&(i - 4)), Some(i - 4));
+            }
+            assert_eq!(map.get(&i), Some(&i));
+        }
+
+        assert_eq!(map.vec.len(), 1 << MIN_SIZE_SHIFT);
+        assert_eq!(map.used, 4);
+        for i in 0..9996 {
+            assert_eq!(map.get(&i), None);
+        }
+    }
+
+    #[test]
+    fn with_hasher() {
+        let hasher = fnv::FnvBuildHasher::default();
+        let mut map = HashMap::with_hasher(hasher.clone());
+        map.insert("one", 1);
+        map.insert("two", 2);
+        assert_eq!(map.get("one"), Some(&1));
+        assert_eq!(map.hasher().hash_one("one"), hasher.hash_one("one"));
+
+        let mut map = HashMap::with_capacity_and_hasher(10, hasher.clone());
+        map.insert(1, 1);
+        assert_eq!(map.get(&1), Some(&1));
+
+        let mut set = HashSet::with_hasher(hasher.clone());
+        set.insert(5);
+        assert_eq!(set.get(&5), Some(&5));
+        assert_eq!(set.hasher().hash_one(5), hasher.hash_one(5));
+    }
+
+    /// Hasher using the hashed integer as the hash, to control the collisions
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl std::hash::Hasher for IdentityHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 << 8) | *byte as u64;
+            }
+        }
+
+        fn write_u64(&mut self, i: u64) {
+            self.0 = i;
+        }
+
+        fn finish(&self) -> u64 {
+            self.0
+        }
+    }
+
+    type IdentityMap<V> = HashMap<u64, V, std::hash::BuildHasherDefault<IdentityHasher>>;
+
+    /// Checks that every chain is ordered by the desired index of its items
+    fn check_order<K, V, S>(map: &HashMap<K, V, S>) {
+        let mask = map.vec.len().wrapping_sub(1);
+        let occupied = map.vec.iter().filter(|cell| !cell.is_empty()).count();
+        assert_eq!(occupied, map.used);
+
+        for (idx, cell) in map.vec.iter().enumerate() {
+            let Cell::Item { hash, .. } = cell else {
+                continue;
+            };
+
+            let dist = distance(*hash, idx, mask);
+            match &map.vec[idx.wrapping_sub(1) & mask] {
+                Cell::Empty => assert_eq!(dist, 0),
+                Cell::Item { hash, .. } => {
+                    assert!(dist <= distance(*hash, idx.wrapping_sub(1) & mask, mask) + 1)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn load_factor() {
+        let map: HashMap<u64, u64> = HashMap::with_capacity(0);
+        assert_eq!(map.vec.len(), 0);
+
+        let mut map: HashMap<u64, u64> = HashMap::with_capacity(7);
+        assert_eq!(map.vec.len(), 8);
+
+        for i in 0..7 {
+            map.insert(i, i);
+        }
+        assert_eq!(map.vec.len(), 8);
+
+        map.insert(7, 7);
+        assert_eq!(map.vec.len(), 16);
+
+        let map: HashMap<u64, u64> = HashMap::with_capacity(100);
+        assert_eq!(map.vec.len(), 128);
+    }
+
+    #[test]
+    fn collisions() {
+        let mut map = IdentityMap::default();
+        // All desired at the end of the table, so the chain wraps around
+        for i in 0..6 {
+            map.insert(7 + i * 16, i);
+        }
+        map.insert(0, 10);
+        check_order(&map);
+
+        for i in 0..6 {
+            assert_eq!(map.get(&(7 + i * 16)), Some(&i));
+        }
+        assert_eq!(map.get(&0), Some(&10));
+        assert_eq!(map.get(&(7 + 6 * 16)), None);
+        assert_eq!(map.get(&16), None);
+
+        // Removing from the middle of the wrapped chain shifts the rest back
+        assert_eq!(map.remove(&(7 + 2 * 16)), Some(2));
+        check_order(&map);
+        assert_eq!(map.get(&(7 + 5 * 16)), Some(&5));
+        assert_eq!(map.get(&0), Some(&10));
+
+        assert_eq!(map.remove(&0), Some(10));
+        assert_eq!(map.remove(&7), Some(0));
+        check_order(&map);
+        let mut items: Vec<_> = map.into_iter().collect();
+        items.sort();
+        assert_eq!(items, [(23, 1), (55, 3), (71, 4), (87, 5)]);
+    }
+
+    #[test]
+    fn random_ops() {
+        let mut map = IdentityMap::default();
+        let mut expected = std::collections::HashMap::new();
+
+        let mut rng = 42u64;
+        for _ in 0..20000 {
+            rng = rng
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            // Few distinct, clustered hashes, to get long chains
+            let key = (rng >> 33) % 500 * 3;
+
+            match rng >> 62 {
+                0 | 1 => assert_eq!(map.insert(key, rng), expected.insert(key, rng)),
+                2 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                _ => assert_eq!(map.get(&key), expected.get(&key)),
+            }
+        }
+        check_order(&map);
+
+        let mut items: Vec<_> = map.into_iter().collect();
+        items.sort();
+        let mut expected: Vec<_> = expected.into_iter().collect();
+        expected.sort();
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn len_and_capacity() {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        assert_eq!(map.len(), 0);
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), 0);
+
+        map.reserve(10);
+        assert!(map.capacity() >= 10);
+        let cap = map.capacity();
+
+        for i in 0..10 {
+            map.insert(i, i);
+        }
+        map.insert(5, 10);
+        map.remove(&6);
+        assert_eq!(map.len(), 9);
+        assert!(!map.is_empty());
+        assert_eq!(map.capacity(), cap);
+
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.capacity(), cap);
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        let mut map: HashMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        map.retain(|k, _| *k < 5);
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 7);
+        for i in 0..100 {
+            assert_eq!(map.get(&i), if i < 5 { Some(&i) } else { None });
+        }
+
+        map.clear();
+        map.shrink_to_fit();
+        assert_eq!(map.capacity(), 0);
+        assert_eq!(map.get(&1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn reserve_overflow() {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        map.insert(1, 1);
+        map.reserve(usize::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity overflow")]
+    fn with_capacity_overflow() {
+        let _: HashMap<u64, u64> = HashMap::with_capacity(usize::MAX);
+    }
+
+    #[test]
+    fn contains_key() {
+        let mut map: HashMap<String, u64> = HashMap::new();
+        assert!(!map.contains_key("one"));
+        assert_eq!(map.get_key_value("one"), None);
+
+        map.insert("one".to_owned(), 1);
+        assert!(map.contains_key("one"));
+        assert!(!map.contains_key("two"));
+        assert_eq!(map.get_key_value("one"), Some((&"one".to_owned(), &1)));
+    }
+
+    #[test]
+    fn retain() {
+        let mut map: HashMap<u64, u64> = (0..1000).map(|i| (i, i)).collect();
+        let mut visited = 0;
+        map.retain(|k, v| {
+            visited += 1;
+            *v += 1;
+            k % 3 == 0
+        });
+        assert_eq!(visited, 1000);
+        assert_eq!(map.len(), 334);
+        for i in 0..1000 {
+            assert_eq!(map.get(&i).copied(), (i % 3 == 0).then_some(i + 1));
+        }
+
+        // Chain wrapping around the table end
+        let mut map = IdentityMap::default();
+        for i in 0..6 {
+            map.insert(7 + i * 16, i);
+        }
+        let mut visited = vec![];
+        map.retain(|k, _| {
+            visited.push(*k);
+            k % 32 == 7
+        });
+        visited.sort();
+        assert_eq!(visited, [7, 23, 39, 55, 71, 87]);
+        check_order(&map);
+
+        let mut items: Vec<_> = map.into_keys().collect();
+        items.sort();
+        assert_eq!(items, [7, 39, 71]);
+    }
+
+    #[test]
+    fn index() {
+        let map: HashMap<String, u64> = [("one".to_owned(), 1)].into_iter().collect();
+        assert_eq!(map["one"], 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_missing() {
+        let map: HashMap<u64, u64> = HashMap::new();
+        let _ = map[&1];
+    }
+
+    #[test]
+    fn clone_and_eq() {
+        let map: HashMap<u64, u64> = (0..100).map(|i| (i, i)).collect();
+        let mut other = map.clone();
+        assert_eq!(map, other);
+
+        // Same items inserted in different order
+        let reversed: HashMap<u64, u64> = (0..100).rev().map(|i| (i, i)).collect();
+        assert_eq!(map, reversed);
+
+        other.insert(5, 6);
+        assert_ne!(map, other);
+        other.insert(5, 5);
+        other.insert(100, 100);
+        assert_ne!(map, other);
+    }
+
+    #[test]
+    fn debug() {
+        let mut map: HashMap<u64, u64> = HashMap::new();
+        assert_eq!(format!("{map:?}"), "{}");
+
+        map.insert(1, 2);
+        assert_eq!(format!("{map:?}"), "{1: 2}");
+    }
 }
@@ -0,0 +1,589 @@
+//! Hash set, implemented as the `HashMap` with no values
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::Chain;
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+use crate::{Cell, HashMap};
+
+#[derive(Clone)]
+pub struct HashSet<T, S = RandomState>(HashMap<T, (), S>);
+
+impl<T> HashSet<T> {
+    pub fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        Self(HashMap::with_capacity(cap))
+    }
+}
+
+impl<T, S> HashSet<T, S> {
+    /// Creates an empty set, using `hasher_builder` to hash the values
+    pub fn with_hasher(hasher_builder: S) -> Self {
+        Self(HashMap::with_hasher(hasher_builder))
+    }
+
+    pub fn with_capacity_and_hasher(cap: usize, hasher_builder: S) -> Self {
+        Self(HashMap::with_capacity_and_hasher(cap, hasher_builder))
+    }
+
+    /// Returns the hasher builder used by the set
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of values the set can hold without reallocating
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Removes all the values, keeping the allocated memory
+    pub fn clear(&mut self) {
+        self.0.clear()
+    }
+
+    /// Keeps only the values for which `f` returns `true`, visiting every value once
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.0.retain(|value, _| f(value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.0.keys())
+    }
+
+    /// Removes all the values, returning them in an iterator. The allocated memory is kept.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        Drain(self.0.drain())
+    }
+}
+
+impl<T, S> HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    /// Reserves capacity for at least `additional` more values
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    /// Shrinks the memory to the smallest size fitting all the values
+    pub fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.contains_key(value)
+    }
+
+    pub fn get<Q>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.0.get_key_value(value).map(|(value, _)| value)
+    }
+
+    /// Adds the value to the set, returning `false` if it was already there. In such case the
+    /// set is not modified.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.0.insert(value, ()).is_none()
+    }
+
+    /// Adds the value to the set, replacing and returning the equal value if there was one
+    pub fn replace(&mut self, mut value: T) -> Option<T> {
+        let map = &mut self.0;
+        let hash = map.hasher_builder.hash_one(&value);
+
+        match map.find(hash, &value) {
+            Some(idx) => {
+                let Cell::Item { key, .. } = &mut map.vec[idx] else {
+                    unreachable!()
+                };
+                std::mem::swap(key, &mut value);
+                Some(value)
+            }
+            None => {
+                map.insert(value, ());
+                None
+            }
+        }
+    }
+
+    /// Removes the value, returning `true` if it was in the set
+    pub fn remove<Q>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.take(value).is_some()
+    }
+
+    /// Removes and returns the value equal to the given one
+    pub fn take<Q>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let map = &mut self.0;
+        let idx = map.find(map.hasher_builder.hash_one(value), value)?;
+        let Cell::Item { key, .. } = crate::remove_cell(&mut map.vec, idx) else {
+            unreachable!()
+        };
+        map.used -= 1;
+
+        Some(key)
+    }
+
+    /// Values in `self` or `other`, without duplicates
+    pub fn union<'a>(&'a self, other: &'a Self) -> Union<'a, T, S> {
+        Union(self.iter().chain(other.difference(self)))
+    }
+
+    /// Values both in `self` and `other`
+    pub fn intersection<'a>(&'a self, other: &'a Self) -> Intersection<'a, T, S> {
+        // Iterating over the smaller set, there are less lookups
+        let (smaller, larger) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+
+        Intersection {
+            iter: smaller.iter(),
+            other: larger,
+        }
+    }
+
+    /// Values in `self`, but not in `other`
+    pub fn difference<'a>(&'a self, other: &'a Self) -> Difference<'a, T, S> {
+        Difference {
+            iter: self.iter(),
+            other,
+        }
+    }
+
+    /// Values in `self` or `other`, but not in both
+    pub fn symmetric_difference<'a>(&'a self, other: &'a Self) -> SymmetricDifference<'a, T, S> {
+        SymmetricDifference(self.difference(other).chain(other.difference(self)))
+    }
+
+    /// Returns `true` if all the values of `self` are in `other`
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|value| other.contains(value))
+    }
+
+    /// Returns `true` if all the values of `other` are in `self`
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if `self` and `other` have no common values
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.intersection(other).next().is_none()
+    }
+}
+
+impl<T, S> Default for HashSet<T, S>
+where
+    S: Default,
+{
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<T, S> fmt::Debug for HashSet<T, S>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S> PartialEq for HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<T, S> Eq for HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+}
+
+pub struct Iter<'set, T>(crate::Keys<'set, T, ()>);
+
+impl<'set, T> Iterator for Iter<'set, T> {
+    type Item = &'set T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'set, T, S> IntoIterator for &'set HashSet<T, S> {
+    type Item = &'set T;
+    type IntoIter = Iter<'set, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub struct IntoIter<T>(crate::IntoKeys<T, ()>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T, S> IntoIterator for HashSet<T, S> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_keys())
+    }
+}
+
+pub struct Drain<'set, T>(crate::Drain<'set, T, ()>);
+
+impl<'set, T> Iterator for Drain<'set, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(value, _)| value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T, S> FromIterator<T> for HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|value| (value, ())).collect())
+    }
+}
+
+impl<T, S> Extend<T> for HashSet<T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.0.extend(iter.into_iter().map(|value| (value, ())))
+    }
+}
+
+impl<'a, T, S> Extend<&'a T> for HashSet<T, S>
+where
+    T: Eq + Hash + Copy,
+    S: BuildHasher,
+{
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+pub struct Union<'set, T, S>(Chain<Iter<'set, T>, Difference<'set, T, S>>);
+
+impl<'set, T, S> Iterator for Union<'set, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'set T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+pub struct Intersection<'set, T, S> {
+    iter: Iter<'set, T>,
+    other: &'set HashSet<T, S>,
+}
+
+impl<'set, T, S> Iterator for Intersection<'set, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'set T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.find(|value| other.contains(*value))
+    }
+}
+
+pub struct Difference<'set, T, S> {
+    iter: Iter<'set, T>,
+    other: &'set HashSet<T, S>,
+}
+
+impl<'set, T, S> Iterator for Difference<'set, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'set T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let other = self.other;
+        self.iter.find(|value| !other.contains(*value))
+    }
+}
+
+pub struct SymmetricDifference<'set, T, S>(Chain<Difference<'set, T, S>, Difference<'set, T, S>>);
+
+impl<'set, T, S> Iterator for SymmetricDifference<'set, T, S>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    type Item = &'set T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// Union of the sets
+impl<T, S> BitOr<&HashSet<T, S>> for &HashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = HashSet<T, S>;
+
+    fn bitor(self, rhs: &HashSet<T, S>) -> HashSet<T, S> {
+        self.union(rhs).cloned().collect()
+    }
+}
+
+/// Intersection of the sets
+impl<T, S> BitAnd<&HashSet<T, S>> for &HashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = HashSet<T, S>;
+
+    fn bitand(self, rhs: &HashSet<T, S>) -> HashSet<T, S> {
+        self.intersection(rhs).cloned().collect()
+    }
+}
+
+/// Difference of the sets
+impl<T, S> Sub<&HashSet<T, S>> for &HashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = HashSet<T, S>;
+
+    fn sub(self, rhs: &HashSet<T, S>) -> HashSet<T, S> {
+        self.difference(rhs).cloned().collect()
+    }
+}
+
+/// Symmetric difference of the sets
+impl<T, S> BitXor<&HashSet<T, S>> for &HashSet<T, S>
+where
+    T: Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    type Output = HashSet<T, S>;
+
+    fn bitxor(self, rhs: &HashSet<T, S>) -> HashSet<T, S> {
+        self.symmetric_difference(rhs).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted<'a>(iter: impl Iterator<Item = &'a u64>) -> Vec<u64> {
+        let mut items: Vec<_> = iter.copied().collect();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn insert() {
+        let mut set: HashSet<u64> = HashSet::new();
+        assert!(set.is_empty());
+        assert!(set.insert(1));
+        assert!(set.insert(2));
+        assert!(!set.insert(1));
+
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&1));
+        assert!(!set.contains(&3));
+        assert_eq!(sorted(set.iter()), [1, 2]);
+    }
+
+    #[test]
+    fn take_and_replace() {
+        // Equal, but distinguishable values
+        #[derive(Debug)]
+        struct Item(u64, &'static str);
+
+        impl PartialEq for Item {
+            fn eq(&self, other: &Self) -> bool {
+                self.0 == other.0
+            }
+        }
+
+        impl Eq for Item {}
+
+        impl Hash for Item {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.0.hash(state)
+            }
+        }
+
+        let mut set: HashSet<Item> = HashSet::new();
+        assert!(set.replace(Item(1, "first")).is_none());
+        assert!(!set.insert(Item(1, "second")));
+        assert_eq!(set.get(&Item(1, "")).unwrap().1, "first");
+
+        assert_eq!(set.replace(Item(1, "third")).unwrap().1, "first");
+        assert_eq!(set.get(&Item(1, "")).unwrap().1, "third");
+        assert_eq!(set.len(), 1);
+
+        assert_eq!(set.take(&Item(1, "")).unwrap().1, "third");
+        assert!(set.take(&Item(1, "")).is_none());
+        assert!(!set.remove(&Item(1, "")));
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn iterators() {
+        let mut set: HashSet<u64> = (0..10).collect();
+        set.extend([10, 11]);
+        set.extend(&[12]);
+        assert_eq!(sorted((&set).into_iter()), (0..13).collect::<Vec<_>>());
+
+        set.retain(|value| value % 2 == 0);
+        assert_eq!(set.iter().len(), 7);
+        assert_eq!(set.clone().into_iter().len(), 7);
+        assert_eq!(set.drain().skip(2).len(), 5);
+        set.extend([0, 2, 4, 6, 8, 10, 12]);
+
+        let mut values: Vec<_> = set.clone().into_iter().collect();
+        values.sort();
+        assert_eq!(values, [0, 2, 4, 6, 8, 10, 12]);
+
+        let mut values: Vec<_> = set.drain().collect();
+        values.sort();
+        assert_eq!(values, [0, 2, 4, 6, 8, 10, 12]);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn set_algebra() {
+        let a: HashSet<u64> = (0..6).collect();
+        let b: HashSet<u64> = (4..10).collect();
+
+        assert_eq!(sorted(a.union(&b)), (0..10).collect::<Vec<_>>());
+        assert_eq!(sorted(a.intersection(&b)), [4, 5]);
+        assert_eq!(sorted(b.intersection(&a)), [4, 5]);
+        assert_eq!(sorted(a.difference(&b)), [0, 1, 2, 3]);
+        assert_eq!(sorted(b.difference(&a)), [6, 7, 8, 9]);
+        assert_eq!(sorted(a.symmetric_difference(&b)), [0, 1, 2, 3, 6, 7, 8, 9]);
+
+        let empty = HashSet::new();
+        assert_eq!(sorted(a.union(&empty)), (0..6).collect::<Vec<_>>());
+        assert_eq!(a.intersection(&empty).count(), 0);
+        assert_eq!(empty.difference(&a).count(), 0);
+    }
+
+    #[test]
+    fn set_relations() {
+        let a: HashSet<u64> = (0..6).collect();
+        let b: HashSet<u64> = (2..4).collect();
+        let c: HashSet<u64> = (6..8).collect();
+
+        assert!(b.is_subset(&a));
+        assert!(!a.is_subset(&b));
+        assert!(a.is_superset(&b));
+        assert!(a.is_subset(&a));
+        assert!(HashSet::new().is_subset(&a));
+
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&b));
+
+        assert_eq!(a, (0..6).rev().collect());
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn operators() {
+        let a: HashSet<u64> = (0..6).collect();
+        let b: HashSet<u64> = (4..10).collect();
+
+        assert_eq!(&a | &b, (0..10).collect());
+        assert_eq!(&a & &b, [4, 5].into_iter().collect());
+        assert_eq!(&a - &b, (0..4).collect());
+        assert_eq!(&a ^ &b, [0, 1, 2, 3, 6, 7, 8, 9].into_iter().collect());
+    }
+
+    #[test]
+    fn debug() {
+        let mut set: HashSet<u64> = HashSet::new();
+        assert_eq!(format!("{set:?}"), "{}");
+
+        set.insert(1);
+        assert_eq!(format!("{set:?}"), "{1}");
+    }
+}
@@ -0,0 +1,15 @@
+use crate::ArrayVec;
+
+impl<T, const N: usize> std::ops::Deref for ArrayVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.buffer.as_ptr() as *const T, self.len) }
+    }
+}
+
+impl<T, const N: usize> std::ops::DerefMut for ArrayVec<T, N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut T, self.len) }
+    }
+}
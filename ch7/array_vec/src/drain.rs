@@ -0,0 +1,138 @@
+use std::ops::{Range, RangeBounds};
+
+use crate::ArrayVec;
+
+/// Converts the range bounds to the range of indices, panicking if it does not fit in `0..len`
+pub(crate) fn range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    use std::ops::Bound;
+
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => *start + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(end) => *end + 1,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        panic!("Index out of bounds");
+    }
+
+    start..end
+}
+
+/// Safety:
+/// While the `Drain` lives, the `vec.len` is shrinked to the drain start, so leaking it never
+/// exposes moved-from elements. Items in `range` are initialized and owned by the `Drain`, and
+/// the `tail_len` items starting at `tail` are moved back when it is dropped.
+pub struct Drain<'vec, T, const N: usize> {
+    vec: &'vec mut ArrayVec<T, N>,
+    range: Range<usize>,
+    tail: usize,
+    tail_len: usize,
+}
+
+impl<'vec, T, const N: usize> Drain<'vec, T, N> {
+    pub(crate) fn new(vec: &'vec mut ArrayVec<T, N>, range: impl RangeBounds<usize>) -> Self {
+        let Range { start, end } = self::range(range, vec.len);
+
+        let tail_len = vec.len - end;
+        vec.len = start;
+
+        Self {
+            vec,
+            range: start..end,
+            tail: end,
+            tail_len,
+        }
+    }
+}
+
+impl<T, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.range.next()?;
+        Some(unsafe { self.vec.buffer[idx].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T, const N: usize> Drop for Drain<'_, T, N> {
+    fn drop(&mut self) {
+        let remaining = std::mem::replace(&mut self.range, 0..0);
+        let slice = &mut self.vec.buffer[remaining];
+        let slice = slice as *mut [std::mem::MaybeUninit<T>] as *mut [T];
+        unsafe { std::ptr::drop_in_place(slice) };
+
+        let start = self.vec.len;
+        let ptr = self.vec.buffer.as_mut_ptr();
+        unsafe { std::ptr::copy(ptr.add(self.tail), ptr.add(start), self.tail_len) };
+        self.vec.len = start + self.tail_len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn drain() {
+        let mut v: ArrayVec<String, 8> = ArrayVec::new();
+        for i in 0..6 {
+            v.push(i.to_string()).unwrap();
+        }
+
+        let drained: Vec<_> = v.drain(1..3).collect();
+        assert_eq!(drained, ["1", "2"]);
+        assert_eq!(&*v, ["0", "3", "4", "5"]);
+
+        let mut drain = v.drain(2..);
+        assert_eq!(drain.size_hint(), (2, Some(2)));
+        assert_eq!(drain.next(), Some("4".to_owned()));
+        drop(drain);
+        assert_eq!(&*v, ["0", "3"]);
+
+        assert_eq!(v.drain(..).count(), 2);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn drain_drop() {
+        let item = Rc::new(());
+
+        let mut v: ArrayVec<_, 8> = ArrayVec::new();
+        for _ in 0..6 {
+            v.push(item.clone()).unwrap();
+        }
+
+        v.drain(1..4);
+        assert_eq!(v.len(), 3);
+        assert_eq!(Rc::strong_count(&item), 4);
+
+        // Leaking the drain leaks the drained and tail items
+        let mut v: ArrayVec<u32, 8> = ArrayVec::new();
+        for i in 0..6 {
+            v.push(i).unwrap();
+        }
+
+        std::mem::forget(v.drain(1..3));
+        assert_eq!(&*v, [0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn drain_past_end() {
+        let mut v: ArrayVec<u32, 8> = ArrayVec::new();
+        v.push(1).unwrap();
+        v.drain(..2);
+    }
+}
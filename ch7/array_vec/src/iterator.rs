@@ -0,0 +1,91 @@
+use std::mem::{ManuallyDrop, MaybeUninit};
+use std::ops::Range;
+
+use crate::ArrayVec;
+
+/// Safety:
+/// The items of `buffer` in `range` are initialized and owned by the iterator
+pub struct IntoIter<T, const N: usize> {
+    buffer: [MaybeUninit<T>; N],
+    range: Range<usize>,
+}
+
+impl<T, const N: usize> IntoIter<T, N> {
+    fn new(vec: ArrayVec<T, N>) -> Self {
+        let vec = ManuallyDrop::new(vec);
+        // Safety:
+        // The `vec` is never dropped, so the buffer is moved out of it
+        let buffer = unsafe { std::ptr::read(&vec.buffer) };
+
+        Self {
+            buffer,
+            range: 0..vec.len,
+        }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let idx = self.range.next()?;
+        Some(unsafe { self.buffer[idx].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for ArrayVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        let slice = &mut self.buffer[self.range.clone()];
+        let slice = slice as *mut [MaybeUninit<T>] as *mut [T];
+        unsafe { std::ptr::drop_in_place(slice) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn into_iter() {
+        let mut v: ArrayVec<u32, 4> = ArrayVec::new();
+        for i in 0..3 {
+            v.push(i).unwrap();
+        }
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(0));
+        let items: Vec<_> = iter.collect();
+        assert_eq!(items, [1, 2]);
+    }
+
+    #[test]
+    fn into_iter_drop() {
+        let item = Rc::new(());
+
+        let mut v: ArrayVec<_, 4> = ArrayVec::new();
+        for _ in 0..3 {
+            v.push(item.clone()).unwrap();
+        }
+
+        let mut iter = v.into_iter();
+        iter.next();
+        assert_eq!(Rc::strong_count(&item), 3);
+        drop(iter);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+}
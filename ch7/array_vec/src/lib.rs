@@ -0,0 +1,208 @@
+use std::mem::MaybeUninit;
+use std::ptr::drop_in_place;
+
+mod deref;
+mod drain;
+mod iterator;
+
+pub use drain::Drain;
+pub use iterator::IntoIter;
+
+/// Vector storing up to `N` elements inline, never allocating.
+///
+/// Safety:
+/// * `len <= N`
+/// * First `len` items of `buffer` are initialized, all the other items are not
+pub struct ArrayVec<T, const N: usize> {
+    buffer: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> ArrayVec<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Appends an element, or returns it back if there is no space left
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.is_full() {
+            return Err(item);
+        }
+
+        self.buffer[self.len].write(item);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        self.len -= 1;
+        Some(unsafe { self.buffer[self.len].assume_init_read() })
+    }
+
+    /// Inserts an element at `index`, or returns it back if there is no space left
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        if index > self.len {
+            panic!("Index out of bounds");
+        }
+
+        if self.is_full() {
+            return Err(element);
+        }
+
+        let ptr = self.buffer.as_mut_ptr();
+        // Safety:
+        // `index <= len < N`, so shifting `index..len` items by one stays within the buffer
+        unsafe { std::ptr::copy(ptr.add(index), ptr.add(index + 1), self.len - index) };
+        self.buffer[index].write(element);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len {
+            panic!("Index out of bounds");
+        }
+
+        let item = unsafe { self.buffer[index].assume_init_read() };
+        let ptr = self.buffer.as_mut_ptr();
+        unsafe { std::ptr::copy(ptr.add(index + 1), ptr.add(index), self.len - index - 1) };
+        self.len -= 1;
+        item
+    }
+
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let last_idx = self.len - 1;
+        self.swap(index, last_idx);
+        self.pop().unwrap()
+    }
+
+    pub fn clear(&mut self) {
+        let slice: *mut [T] = &mut **self;
+        // Length is updated first, so the panicking drop leaks instead of double dropping
+        self.len = 0;
+        unsafe { drop_in_place(slice) };
+    }
+
+    pub fn drain(&mut self, range: impl std::ops::RangeBounds<usize>) -> Drain<'_, T, N> {
+        Drain::new(self, range)
+    }
+}
+
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+    fn drop(&mut self) {
+        let slice: &mut [T] = self;
+        unsafe { drop_in_place(slice) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn push_pop() {
+        let mut v: ArrayVec<u32, 4> = ArrayVec::new();
+        assert_eq!(v.capacity(), 4);
+        assert_eq!(v.pop(), None);
+
+        for i in 0..4 {
+            assert_eq!(v.push(i), Ok(()));
+        }
+        assert!(v.is_full());
+        assert_eq!(v.push(4), Err(4));
+        assert_eq!(&*v, [0, 1, 2, 3]);
+
+        for i in (0..4).rev() {
+            assert_eq!(v.pop(), Some(i));
+        }
+        assert_eq!(v.pop(), None);
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut v: ArrayVec<String, 4> = ArrayVec::new();
+        v.insert(0, "b".to_owned()).unwrap();
+        v.insert(0, "a".to_owned()).unwrap();
+        v.insert(2, "d".to_owned()).unwrap();
+        v.insert(2, "c".to_owned()).unwrap();
+        assert_eq!(&*v, ["a", "b", "c", "d"]);
+        assert_eq!(v.insert(1, "x".to_owned()), Err("x".to_owned()));
+
+        assert_eq!(v.remove(1), "b");
+        assert_eq!(&*v, ["a", "c", "d"]);
+
+        assert_eq!(v.swap_remove(0), "a");
+        assert_eq!(&*v, ["d", "c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut v: ArrayVec<u32, 4> = ArrayVec::new();
+        v.insert(1, 0).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds() {
+        let mut v: ArrayVec<u32, 4> = ArrayVec::new();
+        v.push(0).unwrap();
+        v.remove(1);
+    }
+
+    #[test]
+    fn drop() {
+        let item = Rc::new(());
+
+        let mut v: ArrayVec<_, 4> = ArrayVec::new();
+        for _ in 0..3 {
+            v.push(item.clone()).unwrap();
+        }
+        assert_eq!(Rc::strong_count(&item), 4);
+
+        std::mem::drop(v.pop());
+        assert_eq!(Rc::strong_count(&item), 3);
+
+        std::mem::drop(v);
+        assert_eq!(Rc::strong_count(&item), 1);
+
+        let mut v: ArrayVec<_, 4> = ArrayVec::new();
+        v.push(item.clone()).unwrap();
+        v.clear();
+        assert_eq!(v.len(), 0);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn zst() {
+        let mut v: ArrayVec<(), 3> = ArrayVec::new();
+        for _ in 0..3 {
+            v.push(()).unwrap();
+        }
+        assert_eq!(v.push(()), Err(()));
+        assert_eq!(v.remove(1), ());
+        assert_eq!(v.len(), 2);
+    }
+}
@@ -0,0 +1,225 @@
+use std::ops::Deref;
+
+use crate::{Deque, IntoIter, IterMut};
+
+/// What happens when pushing into a full `BoundedDeque`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// The pushed item is returned back, and the deque stays untouched
+    Reject,
+    /// The item on the opposite end is evicted to make space for the pushed one
+    Overwrite,
+}
+
+/// Fixed-capacity `Deque`, which never reallocates.
+///
+/// The whole buffer is allocated upfront, so pushing never grows it.
+///
+/// Safety:
+/// `deque.len() <= capacity <= deque.buffer.len()`
+pub struct BoundedDeque<T> {
+    deque: Deque<T>,
+    capacity: usize,
+    overflow: Overflow,
+}
+
+impl<T> BoundedDeque<T> {
+    pub fn new(capacity: usize, overflow: Overflow) -> Self {
+        Self {
+            deque: Deque::with_capacity(capacity),
+            capacity,
+            overflow,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.deque.len() == self.capacity
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn set_overflow(&mut self, overflow: Overflow) {
+        self.overflow = overflow;
+    }
+
+    /// Pushes the item to the back of the deque.
+    ///
+    /// Returns `Ok(Some(front))` if the front item was evicted to make space, and `Err(value)`
+    /// if the deque is full and rejects the item.
+    pub fn push_back(&mut self, value: T) -> Result<Option<T>, T> {
+        if !self.is_full() {
+            self.deque.push_back(value);
+            return Ok(None);
+        }
+
+        match self.overflow {
+            Overflow::Reject => Err(value),
+            // Nothing to evict, so the value is evicted immediately
+            Overflow::Overwrite if self.capacity == 0 => Ok(Some(value)),
+            Overflow::Overwrite => {
+                let evicted = self.deque.pop_front();
+                self.deque.push_back(value);
+                Ok(evicted)
+            }
+        }
+    }
+
+    /// Pushes the item to the front of the deque.
+    ///
+    /// Returns `Ok(Some(back))` if the back item was evicted to make space, and `Err(value)`
+    /// if the deque is full and rejects the item.
+    pub fn push_front(&mut self, value: T) -> Result<Option<T>, T> {
+        if !self.is_full() {
+            self.deque.push_front(value);
+            return Ok(None);
+        }
+
+        match self.overflow {
+            Overflow::Reject => Err(value),
+            Overflow::Overwrite if self.capacity == 0 => Ok(Some(value)),
+            Overflow::Overwrite => {
+                let evicted = self.deque.pop_back();
+                self.deque.push_front(value);
+                Ok(evicted)
+            }
+        }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.deque.pop_back()
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.deque.get_mut(index)
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.deque.iter_mut()
+    }
+
+    pub fn into_inner(self) -> Deque<T> {
+        self.deque
+    }
+}
+
+/// Read-only access to the underlying `Deque`. Mutable access is not provided, as it would allow
+/// to grow the deque over its capacity.
+impl<T> Deref for BoundedDeque<T> {
+    type Target = Deque<T>;
+
+    fn deref(&self) -> &Deque<T> {
+        &self.deque
+    }
+}
+
+impl<T> IntoIterator for BoundedDeque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.deque.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject() {
+        let mut deque = BoundedDeque::new(3, Overflow::Reject);
+        assert_eq!(deque.capacity(), 3);
+        assert!(!deque.is_full());
+
+        assert_eq!(deque.push_back(1), Ok(None));
+        assert_eq!(deque.push_back(2), Ok(None));
+        assert_eq!(deque.push_front(0), Ok(None));
+        assert!(deque.is_full());
+
+        assert_eq!(deque.push_back(3), Err(3));
+        assert_eq!(deque.push_front(-1), Err(-1));
+        assert_eq!(deque.len(), 3);
+
+        assert_eq!(deque.pop_front(), Some(0));
+        assert!(!deque.is_full());
+        assert_eq!(deque.push_back(3), Ok(None));
+
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [1, 2, 3]);
+    }
+
+    #[test]
+    fn overwrite() {
+        let mut deque = BoundedDeque::new(3, Overflow::Overwrite);
+        for i in 0..3 {
+            assert_eq!(deque.push_back(i.to_string()), Ok(None));
+        }
+
+        assert_eq!(deque.push_back("3".to_owned()), Ok(Some("0".to_owned())));
+        assert_eq!(deque.push_back("4".to_owned()), Ok(Some("1".to_owned())));
+        assert_eq!(deque.front().unwrap(), "2");
+        assert_eq!(deque.back().unwrap(), "4");
+
+        assert_eq!(deque.push_front("1".to_owned()), Ok(Some("4".to_owned())));
+        let items: Vec<_> = deque.iter().cloned().collect();
+        assert_eq!(items, ["1", "2", "3"]);
+        assert_eq!(deque.len(), 3);
+    }
+
+    #[test]
+    fn never_reallocates() {
+        // Not a power of two, so there is a spare space in the buffer
+        let mut deque = BoundedDeque::new(10, Overflow::Overwrite);
+        let buffer = deque.deque.buffer.as_ptr();
+        let buffer_len = deque.deque.buffer.len();
+
+        for i in 0..100 {
+            deque.push_back(i).unwrap();
+            if i % 3 == 0 {
+                deque.push_front(-i).unwrap();
+            }
+            assert!(deque.len() <= 10);
+        }
+
+        assert!(deque.is_full());
+        assert_eq!(deque.deque.buffer.as_ptr(), buffer);
+        assert_eq!(deque.deque.buffer.len(), buffer_len);
+    }
+
+    #[test]
+    fn set_overflow() {
+        let mut deque = BoundedDeque::new(2, Overflow::Reject);
+        deque.push_back(1).unwrap();
+        deque.push_back(2).unwrap();
+        assert_eq!(deque.push_back(3), Err(3));
+
+        deque.set_overflow(Overflow::Overwrite);
+        assert_eq!(deque.overflow(), Overflow::Overwrite);
+        assert_eq!(deque.push_back(3), Ok(Some(1)));
+        *deque.get_mut(0).unwrap() = 5;
+        let items: Vec<_> = deque.into_inner().into_iter().collect();
+        assert_eq!(items, [5, 3]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut deque = BoundedDeque::new(0, Overflow::Overwrite);
+        assert!(deque.is_full());
+        assert_eq!(deque.push_back(1), Ok(Some(1)));
+        assert_eq!(deque.push_front(2), Ok(Some(2)));
+        assert!(deque.is_empty());
+
+        let mut deque = BoundedDeque::new(0, Overflow::Reject);
+        assert_eq!(deque.push_back(1), Err(1));
+    }
+}
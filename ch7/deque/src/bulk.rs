@@ -0,0 +1,372 @@
+use std::ops::RangeBounds;
+use std::ptr::drop_in_place;
+
+use crate::drain::Drain;
+use crate::Deque;
+
+/// Drops the slice when dropped, so the second slice is dropped even if dropping the first one
+/// panics
+struct DropSlice<T>(*mut [T]);
+
+impl<T> Drop for DropSlice<T> {
+    fn drop(&mut self) {
+        unsafe { drop_in_place(self.0) };
+    }
+}
+
+impl<T> Deque<T> {
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drops all the items after the first `len` ones
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+
+        let (head, tail) = self.as_slices_mut();
+        let (head, tail): (*mut [T], *mut [T]) = if len < head.len() {
+            (&mut head[len..], tail)
+        } else {
+            let tail_start = len - head.len();
+            (&mut [], &mut tail[tail_start..])
+        };
+
+        // Safety:
+        // Items are considered uninitialized before they are dropped, so they are never dropped
+        // twice, even when unwinding
+        self.len = len;
+        let _tail = DropSlice(tail);
+        unsafe { drop_in_place(head) };
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+        self.head = 0;
+    }
+
+    /// Removes the items in `range`, returning them in an iterator.
+    ///
+    /// The gap is closed by moving either the items before or after the range - whichever side is
+    /// shorter. If the `Drain` is leaked, the items after the range are leaked too.
+    pub fn drain(&mut self, range: impl RangeBounds<usize>) -> Drain<'_, T> {
+        Drain::new(self, range)
+    }
+
+    /// Splits the deque at `at`, returning the items `at..len` in a new deque
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.len {
+            panic!("Index out of bounds");
+        }
+
+        let mut other = Self::with_capacity(self.len - at);
+        let (head, tail) = self.as_slices();
+        let (head, tail) = if at < head.len() {
+            (&head[at..], tail)
+        } else {
+            (&[][..], &tail[at - head.len()..])
+        };
+
+        // Safety:
+        // The `other` deque has capacity for all the moved items, and they are forgotten by
+        // shrinking the `self.len`
+        unsafe {
+            other.copy_slice(0, head);
+            other.copy_slice(head.len(), tail);
+        }
+        other.len = self.len - at;
+        self.len = at;
+
+        other
+    }
+
+    /// Moves all the items of `other` to the back of `self`, leaving the `other` empty
+    pub fn append(&mut self, other: &mut Self) {
+        if other.is_empty() {
+            return;
+        }
+
+        self.grow_to(self.len.checked_add(other.len).expect("capacity overflow"));
+
+        let (head, tail) = other.as_slices();
+        // Safety:
+        // After growing there is space for all the `other` items after the back, and they are
+        // forgotten by zeroing the `other.len`
+        unsafe {
+            self.copy_slice(self.idx(self.len), head);
+            self.copy_slice(self.idx(self.len + head.len()), tail);
+        }
+
+        self.len += other.len;
+        other.len = 0;
+        other.head = 0;
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.retain_mut(|item| f(item))
+    }
+
+    /// Keeps only the items for which `f` returns `true`, preserving their order.
+    ///
+    /// Kept items are swapped to the front, so all the items stay in the deque if `f` panics.
+    pub fn retain_mut(&mut self, mut f: impl FnMut(&mut T) -> bool) {
+        let len = self.len;
+        let mut kept = 0;
+
+        for idx in 0..len {
+            if f(&mut self[idx]) {
+                if idx != kept {
+                    self.swap(idx, kept);
+                }
+                kept += 1;
+            }
+        }
+
+        self.truncate(kept);
+    }
+
+    /// Resizes the deque to `new_len`, filling the new items at the back with the results of `f`
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+
+        self.grow_to(new_len);
+        for _ in self.len..new_len {
+            self.push_back(f());
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more items
+    pub fn reserve(&mut self, additional: usize) {
+        self.grow_to(self.len.checked_add(additional).expect("capacity overflow"));
+    }
+
+    /// Shrinks the buffer to the smallest power of two fitting all the items, releasing it
+    /// completely when the deque is empty
+    pub fn shrink_to_fit(&mut self) {
+        if self.is_empty() {
+            self.buffer = vec![];
+            self.head = 0;
+            return;
+        }
+
+        let mut other = Self::with_capacity(self.len);
+        if other.buffer.len() >= self.buffer.len() {
+            return;
+        }
+
+        let (head, tail) = self.as_slices();
+        // Safety:
+        // The `other` deque has capacity for all the items, and they are forgotten in `self`
+        // before it is dropped
+        unsafe {
+            other.copy_slice(0, head);
+            other.copy_slice(head.len(), tail);
+        }
+        other.len = self.len;
+        self.len = 0;
+
+        *self = other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use crate::test_utils::{items, wrapped_strings};
+    use crate::Deque;
+
+    #[test]
+    fn truncate() {
+        for len in 0..=11 {
+            let mut deque = wrapped_strings(4, 6);
+            deque.truncate(len);
+
+            let expected: Vec<_> = (-4..6).take(len).collect();
+            assert_eq!(items(&deque), expected);
+        }
+
+        let mut deque = wrapped_strings(4, 6);
+        deque.clear();
+        assert!(deque.is_empty());
+        assert_eq!(deque.capacity(), 16);
+        deque.push_back("1".to_owned());
+        assert_eq!(items(&deque), [1]);
+    }
+
+    #[test]
+    fn truncate_panic() {
+        struct Bomb<'a>(bool, &'a Cell<usize>);
+
+        impl Drop for Bomb<'_> {
+            fn drop(&mut self) {
+                self.1.set(self.1.get() + 1);
+                if self.0 {
+                    panic!("Boom");
+                }
+            }
+        }
+
+        let dropped = Cell::new(0);
+        let mut deque = Deque::with_capacity(8);
+        for _ in 0..4 {
+            deque.push_back(Bomb(false, &dropped));
+        }
+        deque.push_front(Bomb(true, &dropped));
+        deque.push_front(Bomb(false, &dropped));
+
+        let res = catch_unwind(AssertUnwindSafe(|| deque.truncate(1)));
+        assert!(res.is_err());
+        assert_eq!(dropped.get(), 5);
+        assert_eq!(deque.len(), 1);
+    }
+
+    #[test]
+    fn split_off() {
+        for at in 0..=10 {
+            let mut deque = wrapped_strings(4, 6);
+            let other = deque.split_off(at);
+
+            let mut expected: Vec<_> = (-4..6).collect();
+            let expected_other = expected.split_off(at);
+            assert_eq!(items(&deque), expected);
+            assert_eq!(items(&other), expected_other);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_off_out_of_bounds() {
+        wrapped_strings(4, 6).split_off(11);
+    }
+
+    #[test]
+    fn append() {
+        let mut deque = wrapped_strings(4, 6);
+        let mut other = wrapped_strings(3, 3);
+        deque.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(
+            items(&deque),
+            [-4, -3, -2, -1, 0, 1, 2, 3, 4, 5, -3, -2, -1, 0, 1, 2]
+        );
+
+        // Wrapping destination without growing
+        let mut deque = Deque::with_capacity(8);
+        for i in 0..6 {
+            deque.push_back(i.to_string());
+        }
+        for _ in 0..4 {
+            deque.pop_front();
+        }
+        let mut other = wrapped_strings(2, 3);
+        deque.append(&mut other);
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(items(&deque), [4, 5, -2, -1, 0, 1, 2]);
+
+        let mut empty = Deque::new();
+        deque.append(&mut empty);
+        empty.append(&mut deque);
+        assert!(deque.is_empty());
+        assert_eq!(items(&empty), [4, 5, -2, -1, 0, 1, 2]);
+    }
+
+    #[test]
+    fn retain() {
+        let mut deque = wrapped_strings(4, 6);
+        deque.retain(|item| item.parse::<i32>().unwrap() % 2 == 0);
+        assert_eq!(items(&deque), [-4, -2, 0, 2, 4]);
+
+        let mut deque = wrapped_strings(4, 6);
+        deque.retain_mut(|item| {
+            item.push('0');
+            item.len() > 2
+        });
+        assert_eq!(items(&deque), [-40, -30, -20, -10]);
+
+        let mut deque = wrapped_strings(4, 6);
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            deque.retain(|item| {
+                if item == "2" {
+                    panic!("Boom");
+                }
+                item != "-3"
+            })
+        }));
+        assert!(res.is_err());
+        assert_eq!(deque.len(), 10);
+    }
+
+    #[test]
+    fn resize_with() {
+        let mut deque = wrapped_strings(2, 2);
+        let mut next = 2;
+        deque.resize_with(20, || {
+            next += 1;
+            (next - 1).to_string()
+        });
+        let expected: Vec<_> = (-2..18).collect();
+        assert_eq!(items(&deque), expected);
+
+        deque.resize_with(3, || unreachable!());
+        assert_eq!(items(&deque), [-2, -1, 0]);
+    }
+
+    #[test]
+    fn reserve_shrink() {
+        let mut deque = wrapped_strings(2, 2);
+        assert_eq!(deque.capacity(), 8);
+
+        deque.reserve(20);
+        assert_eq!(deque.capacity(), 32);
+        assert_eq!(items(&deque), [-2, -1, 0, 1]);
+
+        deque.reserve(4);
+        assert_eq!(deque.capacity(), 32);
+
+        // Wrap the items around the bigger buffer
+        for i in 1..=20 {
+            deque.push_front((-2 - i).to_string());
+        }
+        deque.truncate(9);
+        deque.shrink_to_fit();
+        assert_eq!(deque.capacity(), 16);
+        let expected: Vec<_> = (-22..-13).collect();
+        assert_eq!(items(&deque), expected);
+
+        deque.clear();
+        deque.shrink_to_fit();
+        assert_eq!(deque.capacity(), 0);
+        deque.push_front("1".to_owned());
+        assert_eq!(items(&deque), [1]);
+    }
+
+    #[test]
+    fn released_buffer() {
+        // Every operation has to work on the deque without any buffer
+        let mut deque = wrapped_strings(2, 2);
+        deque.clear();
+        deque.shrink_to_fit();
+
+        deque.rotate_left(0);
+        deque.rotate_right(0);
+        deque.truncate(0);
+        deque.retain(|_| true);
+        assert_eq!(deque.drain(..).count(), 0);
+        assert_eq!(deque.range(..).count(), 0);
+        assert_eq!(deque.remove(0), None);
+        assert_eq!(deque.get(0), None);
+        assert_eq!(deque.split_off(0).len(), 0);
+        deque.append(&mut Deque::new());
+        deque.make_contiguous();
+        assert_eq!(deque.capacity(), 0);
+
+        deque.insert(0, "1".to_owned());
+        assert_eq!(items(&deque), [1]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::hash::{Hash, Hasher};
+
+use crate::Deque;
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other)
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T: Hash> Hash for Deque<T> {
+    /// Hashes the items the same way regardless of where the buffer wraps
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        self.iter().for_each(|item| item.hash(state));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+
+    fn hash(deque: &Deque<i32>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        deque.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn eq_hash() {
+        // Same items, but wrapping in different places
+        let mut wrapped = Deque::with_capacity(8);
+        for i in (0..3).rev() {
+            wrapped.push_front(i);
+        }
+        wrapped.push_back(3);
+        wrapped.push_back(4);
+        let contiguous: Deque<_> = (0..5).collect();
+
+        assert_ne!(wrapped.as_slices(), contiguous.as_slices());
+        assert_eq!(wrapped, contiguous);
+        assert_eq!(hash(&wrapped), hash(&contiguous));
+
+        let shorter: Deque<_> = (0..4).collect();
+        assert_ne!(wrapped, shorter);
+        assert_ne!(hash(&wrapped), hash(&shorter));
+
+        wrapped[2] = 10;
+        assert_ne!(wrapped, contiguous);
+    }
+}
@@ -0,0 +1,186 @@
+use std::ops::{Range, RangeBounds};
+
+use crate::Deque;
+
+/// Converts the range bounds to the range of indices, panicking if it does not fit in `0..len`
+pub(crate) fn range(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+    use std::ops::Bound;
+
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => *start + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(end) => *end + 1,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => len,
+    };
+
+    if start > end || end > len {
+        panic!("Index out of bounds");
+    }
+
+    start..end
+}
+
+/// Safety:
+/// While the `Drain` lives, the `len` of the deque is shrinked to the drain `start`, so leaking it
+/// never exposes moved-from items. Items in `front..back` are not yet yielded, and the `tail_len`
+/// items after the drained range are joined back with the deque when the `Drain` is dropped.
+pub struct Drain<'a, T> {
+    deque: &'a mut Deque<T>,
+    start: usize,
+    front: usize,
+    back: usize,
+    end: usize,
+    tail_len: usize,
+}
+
+impl<'a, T> Drain<'a, T> {
+    pub(crate) fn new(deque: &'a mut Deque<T>, range: impl RangeBounds<usize>) -> Self {
+        let Range { start, end } = self::range(range, deque.len);
+
+        let tail_len = deque.len - end;
+        deque.len = start;
+
+        Self {
+            deque,
+            start,
+            front: start,
+            back: end,
+            end,
+            tail_len,
+        }
+    }
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        // Safety:
+        // Items in `front..back` are initialized, and not yielded yet
+        let idx = self.deque.idx(self.front);
+        self.front += 1;
+        Some(unsafe { self.deque.buffer[idx].assume_init_read() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        let idx = self.deque.idx(self.back);
+        Some(unsafe { self.deque.buffer[idx].assume_init_read() })
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+impl<T> Drop for Drain<'_, T> {
+    fn drop(&mut self) {
+        self.by_ref().for_each(drop);
+
+        let drained = self.end - self.start;
+        if drained > 0 {
+            // Closing the gap by moving the shorter side
+            let deque = &mut *self.deque;
+            if self.start <= self.tail_len {
+                let new_head = deque.idx(drained);
+                unsafe { deque.wrap_copy(deque.head, new_head, self.start) };
+                deque.head = new_head;
+            } else {
+                unsafe {
+                    deque.wrap_copy(deque.idx(self.end), deque.idx(self.start), self.tail_len)
+                };
+            }
+        }
+
+        self.deque.len = self.start + self.tail_len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{items, wrapped_strings};
+    use crate::Deque;
+
+    #[test]
+    fn drain() {
+        for start in 0..=10 {
+            for end in start..=10 {
+                let mut deque = wrapped_strings(4, 6);
+                let drained: Vec<i32> = deque
+                    .drain(start..end)
+                    .map(|item| item.parse().unwrap())
+                    .collect();
+
+                let mut expected: Vec<_> = (-4..6).collect();
+                let expected_drained: Vec<_> = expected.drain(start..end).collect();
+                assert_eq!(drained, expected_drained);
+                assert_eq!(items(&deque), expected);
+                assert_eq!(deque.len(), expected.len());
+            }
+        }
+    }
+
+    #[test]
+    fn partially_consumed() {
+        let mut deque = wrapped_strings(4, 6);
+        let mut drain = deque.drain(2..8);
+        assert_eq!(drain.len(), 6);
+        assert_eq!(drain.next().unwrap(), "-2");
+        assert_eq!(drain.next_back().unwrap(), "3");
+        assert_eq!(drain.len(), 4);
+        drop(drain);
+        assert_eq!(items(&deque), [-4, -3, 4, 5]);
+
+        let mut deque = wrapped_strings(4, 6);
+        deque.drain(..);
+        assert!(deque.is_empty());
+        deque.push_back("1".to_owned());
+        assert_eq!(items(&deque), [1]);
+
+        // Leaking the drain leaks the tail
+        let mut deque: Deque<_> = (0..10).collect();
+        std::mem::forget(deque.drain(3..5));
+        assert_eq!(deque.len(), 3);
+        assert!(deque.iter().eq(&[0, 1, 2]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_bounds() {
+        let mut deque = wrapped_strings(4, 6);
+        let start = deque.len() - 5;
+        deque.drain(start..3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn past_end() {
+        let mut deque = wrapped_strings(4, 6);
+        deque.drain(5..11);
+    }
+
+    #[test]
+    fn empty() {
+        let mut deque: Deque<u32> = Deque::new();
+        assert_eq!(deque.drain(..).count(), 0);
+        assert!(deque.is_empty());
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::{self, BufRead, Read, Write};
+
+use crate::Deque;
+
+impl Deque<u8> {
+    /// Copies the bytes from the front of the deque to `buf` without consuming them, returning the
+    /// number of bytes copied
+    pub fn peek(&self, buf: &mut [u8]) -> usize {
+        let (head, tail) = self.as_slices();
+        let len = buf.len().min(self.len);
+        let head_len = len.min(head.len());
+
+        buf[..head_len].copy_from_slice(&head[..head_len]);
+        buf[head_len..len].copy_from_slice(&tail[..len - head_len]);
+        len
+    }
+}
+
+/// Appends the bytes at the back of the deque
+impl Write for Deque<u8> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.reserve(buf.len());
+        // Safety:
+        // After reserving there is space for `buf.len()` bytes after the back
+        unsafe { self.copy_slice(self.idx(self.len), buf) };
+        self.len += buf.len();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Consumes the bytes from the front of the deque
+impl Read for Deque<u8> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.peek(buf);
+        self.consume(len);
+        Ok(len)
+    }
+
+    /// Reads exactly `buf.len()` bytes, even if they wrap around the buffer. If there are not
+    /// enough bytes, nothing is consumed.
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        if buf.len() > self.len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        self.read(buf).map(drop)
+    }
+
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        let len = self.len;
+        let (head, tail) = self.as_slices();
+        buf.extend_from_slice(head);
+        buf.extend_from_slice(tail);
+        self.clear();
+
+        Ok(len)
+    }
+}
+
+impl BufRead for Deque<u8> {
+    /// Returns the bytes up to the wrap point
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.as_slices().0)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.len);
+        if amt > 0 {
+            self.head = self.idx(amt);
+            self.len -= amt;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::wrapped_from;
+
+    #[test]
+    fn write() {
+        let mut deque = wrapped_from(*b"ab", *b"cd");
+        write!(deque, "efg").unwrap();
+        assert!(deque.iter().eq(b"abcdefg"));
+        assert_eq!(deque.capacity(), 8);
+
+        deque.write_all(b"hijklmnop").unwrap();
+        deque.flush().unwrap();
+        assert!(deque.iter().eq(b"abcdefghijklmnop"));
+
+        let mut deque = Deque::new();
+        assert_eq!(deque.write(b"").unwrap(), 0);
+        assert_eq!(deque.write(b"abc").unwrap(), 3);
+        assert!(deque.iter().eq(b"abc"));
+    }
+
+    #[test]
+    fn read() {
+        let mut deque = wrapped_from(*b"abc", *b"defg");
+        let mut buf = [0; 5];
+        assert_eq!(deque.read(&mut buf).unwrap(), 5);
+        assert_eq!(&buf, b"abcde");
+        assert_eq!(deque.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"fg");
+        assert_eq!(deque.read(&mut buf).unwrap(), 0);
+        assert!(deque.is_empty());
+
+        let mut deque: Deque<u8> = Deque::new();
+        assert_eq!(deque.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn read_exact() {
+        let mut deque = wrapped_from(*b"abc", *b"defg");
+        let mut buf = [0; 4];
+        deque.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abcd");
+
+        let err = deque.read_exact(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(deque.iter().eq(b"efg"));
+
+        let mut buf = [0; 3];
+        assert_eq!(deque.peek(&mut buf), 3);
+        assert_eq!(&buf, b"efg");
+        deque.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"efg");
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn read_to_end() {
+        let mut deque = wrapped_from(*b"abc", *b"defg");
+        let mut buf = b"xyz".to_vec();
+        assert_eq!(deque.read_to_end(&mut buf).unwrap(), 7);
+        assert_eq!(buf, b"xyzabcdefg");
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn buf_read() {
+        let mut deque = wrapped_from(*b"ab\ncd", *b"e\nf");
+        assert_eq!(deque.fill_buf().unwrap(), b"ab\ncd");
+        deque.consume(1);
+        assert_eq!(deque.fill_buf().unwrap(), b"b\ncd");
+
+        let lines: Vec<_> = deque.lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["b", "cde", "f"]);
+
+        let mut deque = wrapped_from(*b"ab", *b"cd");
+        deque.consume(10);
+        assert!(deque.is_empty());
+        assert_eq!(deque.fill_buf().unwrap(), b"");
+    }
+
+    #[test]
+    fn fifo() {
+        // Writing and reading in different chunk sizes, so the items wrap around many times
+        let mut deque = Deque::with_capacity(16);
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut read = vec![];
+
+        for (idx, chunk) in data.chunks(7).enumerate() {
+            deque.write_all(chunk).unwrap();
+            if idx % 2 == 1 {
+                let mut buf = [0; 14];
+                let len = deque.read(&mut buf).unwrap();
+                read.extend_from_slice(&buf[..len]);
+            }
+        }
+        deque.read_to_end(&mut read).unwrap();
+
+        assert_eq!(read, data);
+        assert!(deque.capacity() <= 32);
+    }
+}
@@ -1,6 +1,8 @@
-use std::mem::MaybeUninit;
+use std::iter::FusedIterator;
+use std::ops::{Range, RangeBounds};
+use std::slice;
 
-use crate::{Deque, MIN_SHIFT};
+use crate::{drain, Deque};
 
 pub struct IntoIter<T>(Deque<T>);
 
@@ -10,8 +12,22 @@ impl<T> Iterator for IntoIter<T> {
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop_front()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
 }
 
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
 impl<T> IntoIterator for Deque<T> {
     type Item = T;
     type IntoIter = IntoIter<T>;
@@ -21,44 +37,239 @@ impl<T> IntoIterator for Deque<T> {
     }
 }
 
-pub type Iter<'a, T> = std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>;
+/// Iterates over the items of the `head` slice, and then the `tail` slice
+pub struct Iter<'a, T> {
+    head: slice::Iter<'a, T>,
+    tail: slice::Iter<'a, T>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn new(head: &'a [T], tail: &'a [T]) -> Self {
+        Self {
+            head: head.iter(),
+            tail: tail.iter(),
+        }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            head: self.head.clone(),
+            tail: self.tail.clone(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.head.next().or_else(|| self.tail.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.head.len() + self.tail.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.tail.next_back().or_else(|| self.head.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
 
 impl<'a, T> IntoIterator for &'a Deque<T> {
     type Item = &'a T;
     type IntoIter = Iter<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let (fst, lst) = self.as_slices();
-        fst.iter().chain(lst)
+        let (head, tail) = self.as_slices();
+        Iter::new(head, tail)
+    }
+}
+
+/// Iterates over the items of the `head` slice, and then the `tail` slice
+pub struct IterMut<'a, T> {
+    head: slice::IterMut<'a, T>,
+    tail: slice::IterMut<'a, T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    fn new(head: &'a mut [T], tail: &'a mut [T]) -> Self {
+        Self {
+            head: head.iter_mut(),
+            tail: tail.iter_mut(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.head.next().or_else(|| self.tail.next())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.head.len() + self.tail.len();
+        (len, Some(len))
     }
 }
 
-pub type IterMut<'a, T> = std::iter::Chain<std::slice::IterMut<'a, T>, std::slice::IterMut<'a, T>>;
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.tail.next_back().or_else(|| self.head.next_back())
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+impl<T> FusedIterator for IterMut<'_, T> {}
 
 impl<'a, T> IntoIterator for &'a mut Deque<T> {
     type Item = &'a mut T;
     type IntoIter = IterMut<'a, T>;
 
     fn into_iter(self) -> Self::IntoIter {
-        let (fst, lst) = self.as_slices_mut();
-        fst.iter_mut().chain(lst)
+        let (head, tail) = self.as_slices_mut();
+        IterMut::new(head, tail)
+    }
+}
+
+/// Splits the `start..end` range of the deque into the ranges of its head and tail slices
+fn split_range(
+    head_len: usize,
+    Range { start, end }: Range<usize>,
+) -> (Range<usize>, Range<usize>) {
+    let head = start.min(head_len)..end.min(head_len);
+    let tail = start.saturating_sub(head_len)..end.saturating_sub(head_len);
+    (head, tail)
+}
+
+impl<T> Deque<T> {
+    /// Iterates over the items in the `range`
+    pub fn range(&self, range: impl RangeBounds<usize>) -> Iter<'_, T> {
+        let (head, tail) = self.as_slices();
+        let (head_range, tail_range) = split_range(head.len(), drain::range(range, self.len));
+        Iter::new(&head[head_range], &tail[tail_range])
+    }
+
+    /// Iterates mutably over the items in the `range`
+    pub fn range_mut(&mut self, range: impl RangeBounds<usize>) -> IterMut<'_, T> {
+        let range = drain::range(range, self.len);
+        let (head, tail) = self.as_slices_mut();
+        let (head_range, tail_range) = split_range(head.len(), range);
+        IterMut::new(&mut head[head_range], &mut tail[tail_range])
     }
 }
 
 impl<T> FromIterator<T> for Deque<T> {
     fn from_iter<U: IntoIterator<Item = T>>(iter: U) -> Self {
-        let mut buffer: Vec<_> = iter
-            .into_iter()
-            .map(|item| MaybeUninit::new(item))
-            .collect();
-        let len = buffer.len();
-        let shift = Self::make_shift(MIN_SHIFT, buffer.len());
-        buffer.resize_with(shift, || MaybeUninit::uninit());
+        Vec::from_iter(iter).into()
+    }
+}
 
-        Self {
-            buffer,
-            head: 0,
-            len,
+impl<T> Extend<T> for Deque<T> {
+    fn extend<U: IntoIterator<Item = T>>(&mut self, iter: U) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|item| self.push_back(item));
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for Deque<T> {
+    fn extend<U: IntoIterator<Item = &'a T>>(&mut self, iter: U) {
+        self.extend(iter.into_iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::wrapped;
+    use crate::Deque;
+
+    #[test]
+    fn double_ended() {
+        let deque = wrapped(3, 3);
+        let mut iter = deque.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next_back(), Some(&2));
+        assert_eq!(iter.next(), Some(&-3));
+        assert_eq!(iter.len(), 4);
+
+        let rest: Vec<_> = iter.rev().copied().collect();
+        assert_eq!(rest, [1, 0, -1, -2]);
+
+        let mut deque = wrapped(3, 3);
+        let mut iter = deque.iter_mut();
+        *iter.next_back().unwrap() = 10;
+        *iter.next().unwrap() = 20;
+        assert_eq!(iter.len(), 4);
+        iter.rev().for_each(|item| *item *= 2);
+        assert!(deque.iter().eq(&[20, -4, -2, 0, 2, 10]));
+
+        let mut iter = deque.into_iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next_back(), Some(10));
+        assert_eq!(iter.next(), Some(20));
+        let rest: Vec<_> = iter.rev().collect();
+        assert_eq!(rest, [2, 0, -2, -4]);
+    }
+
+    #[test]
+    fn fused() {
+        let deque = wrapped(1, 1);
+        let mut iter = deque.iter();
+        assert_eq!(iter.next(), Some(&-1));
+        assert_eq!(iter.next_back(), Some(&0));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = deque.into_iter();
+        assert_eq!(iter.next_back(), Some(0));
+        assert_eq!(iter.next_back(), Some(-1));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn range() {
+        let deque = wrapped(4, 6);
+        for start in 0..=10 {
+            for end in start..=10 {
+                let items: Vec<_> = deque.range(start..end).copied().collect();
+                let expected: Vec<_> = (-4..6).skip(start).take(end - start).collect();
+                assert_eq!(items, expected);
+                assert_eq!(deque.range(start..end).len(), end - start);
+            }
         }
+
+        assert!(deque.range(..=2).eq(&[-4, -3, -2]));
+        assert!(deque.range(8..).rev().eq(&[5, 4]));
+
+        let mut deque = wrapped(4, 6);
+        deque.range_mut(2..6).for_each(|item| *item *= 10);
+        assert!(deque.iter().eq(&[-4, -3, -20, -10, 0, 10, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn extend() {
+        let mut deque = wrapped(2, 2);
+        deque.extend(2..10);
+        deque.extend(&[10, 11]);
+        let expected: Vec<_> = (-2..12).collect();
+        assert!(deque.iter().eq(&expected));
+
+        let mut deque: Deque<String> = Deque::new();
+        deque.extend(["a".to_owned(), "b".to_owned()]);
+        deque.extend(std::iter::once("c".to_owned()));
+        assert!(deque.iter().eq(["a", "b", "c"].iter()));
     }
 }
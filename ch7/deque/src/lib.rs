@@ -1,9 +1,23 @@
-use std::mem::{transmute, MaybeUninit};
-use std::ptr::drop_in_place;
+use std::cmp::Ordering;
+use std::mem::{transmute, ManuallyDrop, MaybeUninit};
+use std::ops::{Index, IndexMut};
 
 const MIN_SHIFT: usize = 3;
 
+mod bounded;
+mod bulk;
+mod cmp;
+mod drain;
+mod io;
 mod iterator;
+mod monotonic;
+pub mod spsc;
+pub mod steal;
+
+pub use bounded::{BoundedDeque, Overflow};
+pub use drain::Drain;
+pub use iterator::{IntoIter, Iter, IterMut};
+pub use monotonic::{Max, Min, MonotonicDeque, Order, Windows, WindowsExt, WindowsMax, WindowsMin};
 
 // Safety:
 //
@@ -68,31 +82,26 @@ impl<T> Deque<T> {
             let newlen = self.buffer.len();
             let head_len = len - self.head;
             let tail_len = self.head + self.len - len;
+            let ptr = self.buffer.as_mut_ptr();
 
             if head_len <= tail_len {
                 unsafe {
                     std::ptr::copy_nonoverlapping(
-                        &self.buffer[self.head] as _,
-                        &mut self.buffer[newlen - head_len] as _,
+                        ptr.add(self.head),
+                        ptr.add(newlen - head_len),
                         head_len,
                     )
                 };
                 self.head = newlen - head_len;
             } else {
-                unsafe {
-                    std::ptr::copy_nonoverlapping(
-                        &self.buffer[0] as _,
-                        &mut self.buffer[len] as _,
-                        tail_len,
-                    )
-                };
+                unsafe { std::ptr::copy_nonoverlapping(ptr, ptr.add(len), tail_len) };
             }
         }
     }
 
     pub fn push_back(&mut self, value: T) {
         self.grow_to(self.len() + 1);
-        let mask = self.buffer.len() - 1;
+        let mask = self.mask();
         self.buffer[(self.head + self.len) & mask].write(value);
         self.len += 1;
     }
@@ -100,7 +109,7 @@ impl<T> Deque<T> {
     pub fn pop_back(&mut self) -> Option<T> {
         if !self.is_empty() {
             self.len -= 1;
-            let mask = self.buffer.len() - 1;
+            let mask = self.mask();
             let item = unsafe { self.buffer[(self.head + self.len) & mask].assume_init_read() };
             Some(item)
         } else {
@@ -110,7 +119,7 @@ impl<T> Deque<T> {
 
     pub fn push_front(&mut self, value: T) {
         self.grow_to(self.len() + 1);
-        self.head = self.head.wrapping_sub(1) & (self.buffer.len() - 1);
+        self.head = self.head.wrapping_sub(1) & self.mask();
         self.buffer[self.head].write(value);
         self.len += 1;
     }
@@ -118,7 +127,7 @@ impl<T> Deque<T> {
     pub fn pop_front(&mut self) -> Option<T> {
         if !self.is_empty() {
             let item = unsafe { self.buffer[self.head].assume_init_read() };
-            self.head = (self.head + 1) & (self.buffer.len() - 1);
+            self.head = (self.head + 1) & self.mask();
             self.len -= 1;
             Some(item)
         } else {
@@ -126,9 +135,64 @@ impl<T> Deque<T> {
         }
     }
 
+    /// Mask wrapping indices around the buffer. The buffer of the empty deque may be not
+    /// allocated, in such case every index is masked to `0`.
+    fn mask(&self) -> usize {
+        self.buffer.len().saturating_sub(1)
+    }
+
+    /// Buffer index of the item at `index`
+    fn idx(&self, index: usize) -> usize {
+        (self.head + index) & self.mask()
+    }
+
+    /// Moves `count` items from the buffer index `src` to `dst`, both wrapping around the buffer.
+    /// Source and destination may overlap.
+    ///
+    /// Safety:
+    /// Source items have to be initialized, and after the call they are considered
+    /// uninitialized unless overwritten.
+    unsafe fn wrap_copy(&mut self, src: usize, dst: usize, count: usize) {
+        if src == dst || count == 0 {
+            return;
+        }
+
+        let mask = self.mask();
+        let ptr = self.buffer.as_mut_ptr();
+        let copy = |i: usize| {
+            std::ptr::copy_nonoverlapping(ptr.add((src + i) & mask), ptr.add((dst + i) & mask), 1)
+        };
+
+        // If the destination starts within the source, items have to be copied from the back not
+        // to overwrite them before they are moved
+        if (dst.wrapping_sub(src) & mask) < count {
+            (0..count).rev().for_each(copy);
+        } else {
+            (0..count).for_each(copy);
+        }
+    }
+
+    /// Copies the `src` items into the buffer, starting at the buffer index `dst` and wrapping
+    /// around.
+    ///
+    /// Safety:
+    /// There has to be space for `src.len()` items starting at `dst`, and those are considered
+    /// uninitialized. After the call the `src` items are considered moved.
+    unsafe fn copy_slice(&mut self, dst: usize, src: &[T]) {
+        if src.is_empty() {
+            return;
+        }
+
+        let dst = dst & self.mask();
+        let first = src.len().min(self.buffer.len() - dst);
+        let ptr = self.buffer.as_mut_ptr() as *mut T;
+        std::ptr::copy_nonoverlapping(src.as_ptr(), ptr.add(dst), first);
+        std::ptr::copy_nonoverlapping(src.as_ptr().add(first), ptr, src.len() - first);
+    }
+
     pub fn get(&self, index: usize) -> Option<&T> {
         if index < self.len {
-            let mask = self.buffer.len() - 1;
+            let mask = self.mask();
             let item = unsafe { self.buffer[(index + self.head) & mask].assume_init_ref() };
             Some(item)
         } else {
@@ -136,9 +200,9 @@ impl<T> Deque<T> {
         }
     }
 
-    pub fn get_mut(&mut self, index: usize) -> Option<&T> {
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
         if index < self.len {
-            let mask = self.buffer.len() - 1;
+            let mask = self.mask();
             let item = unsafe { self.buffer[(index + self.head) & mask].assume_init_mut() };
             Some(item)
         } else {
@@ -146,6 +210,164 @@ impl<T> Deque<T> {
         }
     }
 
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.wrapping_sub(1))
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.len.wrapping_sub(1))
+    }
+
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let (head, tail) = self.as_slices();
+        head.contains(x) || tail.contains(x)
+    }
+
+    /// Binary searches the sorted deque with the comparator function, like the
+    /// `slice::binary_search_by`.
+    ///
+    /// As the whole deque is sorted, the first item of the tail slice tells which of the slices
+    /// may contain the searched item.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let (head, tail) = self.as_slices();
+
+        match tail.first().map(&mut f) {
+            Some(Ordering::Equal) => Ok(head.len()),
+            Some(Ordering::Less) => tail
+                .binary_search_by(f)
+                .map(|idx| idx + head.len())
+                .map_err(|idx| idx + head.len()),
+            _ => head.binary_search_by(f),
+        }
+    }
+
+    /// Inserts an item at `index`, shifting either the items before or after it - whichever
+    /// side is shorter.
+    pub fn insert(&mut self, index: usize, value: T) {
+        if index > self.len {
+            panic!("Index out of bounds");
+        }
+
+        self.grow_to(self.len + 1);
+
+        if index < self.len - index {
+            let mask = self.mask();
+            let old_head = self.head;
+            self.head = self.head.wrapping_sub(1) & mask;
+            unsafe { self.wrap_copy(old_head, self.head, index) };
+        } else {
+            unsafe { self.wrap_copy(self.idx(index), self.idx(index + 1), self.len - index) };
+        }
+
+        let idx = self.idx(index);
+        self.buffer[idx].write(value);
+        self.len += 1;
+    }
+
+    /// Removes an item at `index`, shifting either the items before or after it - whichever
+    /// side is shorter.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        let item = unsafe { self.buffer[self.idx(index)].assume_init_read() };
+
+        if index < self.len - index - 1 {
+            let new_head = self.idx(1);
+            unsafe { self.wrap_copy(self.head, new_head, index) };
+            self.head = new_head;
+        } else {
+            unsafe { self.wrap_copy(self.idx(index + 1), self.idx(index), self.len - index - 1) };
+        }
+
+        self.len -= 1;
+        Some(item)
+    }
+
+    pub fn swap(&mut self, i: usize, j: usize) {
+        if i >= self.len || j >= self.len {
+            panic!("Index out of bounds");
+        }
+
+        let (i, j) = (self.idx(i), self.idx(j));
+        self.buffer.swap(i, j);
+    }
+
+    /// Removes an item at `index`, replacing it with the first item
+    pub fn swap_remove_front(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.swap(index, 0);
+        self.pop_front()
+    }
+
+    /// Removes an item at `index`, replacing it with the last item
+    pub fn swap_remove_back(&mut self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+
+        self.swap(index, self.len - 1);
+        self.pop_back()
+    }
+
+    /// Rotates the deque `n` places to the left, so the item at `n` becomes the first one
+    pub fn rotate_left(&mut self, n: usize) {
+        if n > self.len {
+            panic!("Index out of bounds");
+        }
+
+        if n == 0 {
+            return;
+        }
+
+        if n <= self.len - n {
+            // Moving first `n` items after the last one
+            unsafe { self.wrap_copy(self.head, self.idx(self.len), n) };
+            self.head = self.idx(n);
+        } else {
+            self.rotate_right(self.len - n);
+        }
+    }
+
+    /// Rotates the deque `n` places to the right, so the item at `len - n` becomes the first one
+    pub fn rotate_right(&mut self, n: usize) {
+        if n > self.len {
+            panic!("Index out of bounds");
+        }
+
+        if n == 0 {
+            return;
+        }
+
+        if n <= self.len - n {
+            // Moving last `n` items before the first one
+            let mask = self.mask();
+            let new_head = self.head.wrapping_sub(n) & mask;
+            unsafe { self.wrap_copy(self.idx(self.len - n), new_head, n) };
+            self.head = new_head;
+        } else {
+            self.rotate_left(self.len - n);
+        }
+    }
+
     pub fn as_slices(&self) -> (&[T], &[T]) {
         if self.head + self.len <= self.buffer.len() {
             let slice = &self.buffer[self.head..self.head + self.len];
@@ -181,7 +403,7 @@ impl<T> Deque<T> {
     }
 
     pub fn make_contiguous(&mut self) -> &mut [T] {
-        if self.head + self.len < self.buffer.len() {
+        if self.head + self.len <= self.buffer.len() {
             let slice = &mut self.buffer[self.head..self.head + self.len];
             let slice: &mut [T] = unsafe { transmute(slice) };
             return slice;
@@ -190,42 +412,22 @@ impl<T> Deque<T> {
         let space = self.buffer.len() - self.len();
         let head_len = self.buffer.len() - self.head;
         let tail_len = self.len + self.head - self.buffer.len();
+        // All the pointers are derived from this one, so the copies don't invalidate each other
+        let ptr = self.buffer.as_mut_ptr();
 
         if space >= head_len {
             // There is a space to copy the head
             unsafe {
-                std::ptr::copy(
-                    &self.buffer[0] as *const _,
-                    &mut self.buffer[head_len] as *mut _,
-                    tail_len,
-                );
-            }
-
-            unsafe {
-                std::ptr::copy_nonoverlapping(
-                    &self.buffer[self.head] as *const _,
-                    &mut self.buffer[0] as *mut _,
-                    head_len,
-                );
+                std::ptr::copy(ptr, ptr.add(head_len), tail_len);
+                std::ptr::copy_nonoverlapping(ptr.add(self.head), ptr, head_len);
             }
 
             self.head = 0;
         } else if space >= tail_len {
             // There is a space to copy the tail
             unsafe {
-                std::ptr::copy(
-                    &self.buffer[self.head] as *const _,
-                    &mut self.buffer[tail_len] as *mut _,
-                    head_len,
-                );
-            }
-
-            unsafe {
-                std::ptr::copy_nonoverlapping(
-                    &self.buffer[0] as *const _,
-                    &mut self.buffer[head_len + tail_len] as *mut _,
-                    tail_len,
-                )
+                std::ptr::copy(ptr.add(self.head), ptr.add(tail_len), head_len);
+                std::ptr::copy_nonoverlapping(ptr, ptr.add(head_len + tail_len), tail_len);
             }
 
             self.head = tail_len;
@@ -234,11 +436,7 @@ impl<T> Deque<T> {
             // to glue it with tail, and then rotating right to fix the order
             if space != 0 {
                 unsafe {
-                    std::ptr::copy(
-                        &self.buffer[self.head] as *const _,
-                        &mut self.buffer[tail_len] as *mut _,
-                        head_len,
-                    );
+                    std::ptr::copy(ptr.add(self.head), ptr.add(tail_len), head_len);
                 }
             }
 
@@ -249,11 +447,7 @@ impl<T> Deque<T> {
             // to glue it with head, and then rotating left to fix the order
             if space != 0 {
                 unsafe {
-                    std::ptr::copy(
-                        &self.buffer[0] as *const _,
-                        &mut self.buffer[space] as *mut _,
-                        tail_len,
-                    );
+                    std::ptr::copy(ptr, ptr.add(space), tail_len);
                 }
             }
 
@@ -266,11 +460,11 @@ impl<T> Deque<T> {
         slice
     }
 
-    pub fn iter(&self) -> iterator::Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         self.into_iter()
     }
 
-    pub fn iter_mut(&mut self) -> iterator::IterMut<T> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         self.into_iter()
     }
 
@@ -288,22 +482,119 @@ impl<T> Default for Deque<T> {
     }
 }
 
+impl<T: Clone> Clone for Deque<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<T> From<Vec<T>> for Deque<T> {
+    /// Reuses the `Vec` buffer, growing it to the power of two length if needed
+    fn from(vec: Vec<T>) -> Self {
+        let len = vec.len();
+        let mut vec = ManuallyDrop::new(vec);
+
+        // Safety:
+        // `MaybeUninit<T>` has the same layout as `T`, and the `vec` is never dropped, so the
+        // buffer has a single owner
+        let mut buffer = unsafe {
+            Vec::from_raw_parts(vec.as_mut_ptr() as *mut MaybeUninit<T>, len, vec.capacity())
+        };
+        buffer.resize_with(Self::make_shift(0, len), MaybeUninit::uninit);
+
+        Self {
+            buffer,
+            head: 0,
+            len,
+        }
+    }
+}
+
+impl<T> Index<usize> for Deque<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("Index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for Deque<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        self.get_mut(index).expect("Index out of bounds")
+    }
+}
+
 impl<T> Drop for Deque<T> {
     fn drop(&mut self) {
-        let len = self.buffer.len();
+        self.truncate(0);
+    }
+}
 
-        if self.head + self.len <= len {
-            let slice = &mut self.buffer[self.head..self.head + self.len];
-            let slice: &mut [T] = unsafe { transmute(slice) };
-            unsafe { drop_in_place(slice) };
-        } else {
-            let slice = &mut self.buffer[..self.head + self.len - len];
-            let slice: &mut [T] = unsafe { transmute(slice) };
-            unsafe { drop_in_place(slice) };
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use crate::Deque;
+
+    /// Deque of the `front` items followed by the `back` items, with the `front` ones pushed to the
+    /// front, so they wrap around the buffer
+    pub(crate) fn wrapped_from<T>(
+        front: impl IntoIterator<Item = T>,
+        back: impl IntoIterator<Item = T>,
+    ) -> Deque<T> {
+        let front: Vec<_> = front.into_iter().collect();
+        let back: Vec<_> = back.into_iter().collect();
+
+        let mut deque = Deque::with_capacity(front.len() + back.len());
+        for item in back {
+            deque.push_back(item);
+        }
+        for item in front.into_iter().rev() {
+            deque.push_front(item);
+        }
+        deque
+    }
 
-            let slice = &mut self.buffer[self.head..];
-            let slice: &mut [T] = unsafe { transmute(slice) };
-            unsafe { drop_in_place(slice) };
+    /// Items `-front..back`, wrapping around the buffer
+    pub(crate) fn wrapped(front: i32, back: i32) -> Deque<i32> {
+        wrapped_from(-front..0, 0..back)
+    }
+
+    /// Items `-front..back` as strings, wrapping around the buffer
+    pub(crate) fn wrapped_strings(front: i32, back: i32) -> Deque<String> {
+        wrapped_from(
+            (-front..0).map(|i| i.to_string()),
+            (0..back).map(|i| i.to_string()),
+        )
+    }
+
+    /// Parses back the items of `wrapped_strings`
+    pub(crate) fn items(deque: &Deque<String>) -> Vec<i32> {
+        deque.iter().map(|item| item.parse().unwrap()).collect()
+    }
+
+    /// Reproducible pseudo-random numbers - the upper 31 bits of a 64-bit LCG
+    pub(crate) fn random(seed: u64) -> impl Iterator<Item = u64> {
+        std::iter::successors(Some(seed), |x| {
+            Some(
+                x.wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407),
+            )
+        })
+        .skip(1)
+        .map(|x| x >> 33)
+    }
+
+    /// Size of the stress test - Miri is too slow for the full run
+    pub(crate) const fn miri_or(miri: usize, full: usize) -> usize {
+        if cfg!(miri) {
+            miri
+        } else {
+            full
         }
     }
 }
@@ -311,6 +602,7 @@ impl<T> Drop for Deque<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{miri_or, random, wrapped};
 
     #[test]
     fn empty() {
@@ -826,4 +1118,275 @@ mod tests {
         assert_eq!(head, &expected);
         assert_eq!(tail, &[]);
     }
+
+    #[test]
+    fn insert() {
+        // Closer to the front
+        let mut deque = wrapped(3, 10);
+        deque.insert(2, 100);
+        let items: Vec<_> = deque.iter().copied().collect();
+        assert_eq!(items, [-3, -2, 100, -1, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+        // Closer to the back
+        let mut deque = wrapped(10, 3);
+        deque.insert(11, 100);
+        let items: Vec<_> = deque.iter().copied().collect();
+        assert_eq!(
+            items,
+            [-10, -9, -8, -7, -6, -5, -4, -3, -2, -1, 0, 100, 1, 2]
+        );
+
+        // Ends
+        let mut deque = wrapped(2, 2);
+        deque.insert(0, 100);
+        deque.insert(5, 101);
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [100, -2, -1, 0, 1, 101]);
+
+        // Growing
+        let mut deque = wrapped(4, 4);
+        for i in 0..20 {
+            deque.insert(4, i);
+        }
+        assert_eq!(deque.len(), 28);
+        let items: Vec<_> = deque.into_iter().collect();
+        let expected: Vec<_> = (-4..0).chain((0..20).rev()).chain(0..4).collect();
+        assert_eq!(items, expected);
+
+        let mut deque = Deque::new();
+        deque.insert(0, "a".to_owned());
+        deque.insert(0, "b".to_owned());
+        deque.insert(2, "c".to_owned());
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, ["b", "a", "c"]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_out_of_bounds() {
+        let mut deque = wrapped(2, 2);
+        deque.insert(5, 0);
+    }
+
+    #[test]
+    fn remove() {
+        let mut deque = wrapped(3, 10);
+        assert_eq!(deque.remove(1), Some(-2));
+        assert_eq!(deque.remove(9), Some(7));
+        assert_eq!(deque.remove(10), Some(9));
+        assert_eq!(deque.remove(10), None);
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [-3, -1, 0, 1, 2, 3, 4, 5, 6, 8]);
+
+        let mut deque: Deque<u32> = Deque::new();
+        assert_eq!(deque.remove(0), None);
+    }
+
+    #[test]
+    fn swap() {
+        let mut deque = wrapped(3, 3);
+        deque.swap(0, 5);
+        deque.swap(1, 3);
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [2, 0, -1, -2, 1, -3]);
+    }
+
+    #[test]
+    fn swap_remove() {
+        let mut deque = wrapped(3, 3);
+        assert_eq!(deque.swap_remove_front(4), Some(1));
+        assert_eq!(deque.swap_remove_back(0), Some(-2));
+        assert_eq!(deque.swap_remove_back(10), None);
+        assert_eq!(deque.swap_remove_front(10), None);
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [2, -1, 0, -3]);
+    }
+
+    #[test]
+    fn rotate() {
+        for n in 0..=10 {
+            let mut deque = wrapped(4, 6);
+            deque.rotate_left(n);
+            let mut expected: Vec<_> = (-4..6).collect();
+            expected.rotate_left(n);
+            let items: Vec<_> = deque.into_iter().collect();
+            assert_eq!(items, expected);
+
+            let mut deque = wrapped(4, 6);
+            deque.rotate_right(n);
+            let mut expected: Vec<_> = (-4..6).collect();
+            expected.rotate_right(n);
+            let items: Vec<_> = deque.into_iter().collect();
+            assert_eq!(items, expected);
+        }
+
+        // Full buffer
+        let mut deque = wrapped(3, 5);
+        deque.rotate_left(3);
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [0, 1, 2, 3, 4, -3, -2, -1]);
+
+        let mut deque: Deque<u32> = Deque::new();
+        deque.rotate_left(0);
+        deque.rotate_right(0);
+    }
+
+    #[test]
+    fn random_access_against_std() {
+        use std::collections::VecDeque;
+
+        let mut deque = Deque::new();
+        let mut expected = VecDeque::new();
+
+        let mut random = random(42);
+        let mut rand = move |max: usize| random.next().unwrap() as usize % max;
+
+        let ops = miri_or(100, 2000);
+        for i in 0..ops {
+            match rand(6) {
+                0 | 1 => {
+                    let idx = rand(expected.len() + 1);
+                    deque.insert(idx, i.to_string());
+                    expected.insert(idx, i.to_string());
+                }
+                2 if !expected.is_empty() => {
+                    let idx = rand(expected.len());
+                    assert_eq!(deque.remove(idx), expected.remove(idx));
+                }
+                3 => {
+                    let n = rand(expected.len() + 1);
+                    deque.rotate_left(n);
+                    expected.rotate_left(n);
+                }
+                4 => {
+                    let n = rand(expected.len() + 1);
+                    deque.rotate_right(n);
+                    expected.rotate_right(n);
+                }
+                _ => {
+                    deque.push_front(i.to_string());
+                    expected.push_front(i.to_string());
+                }
+            }
+
+            assert!(deque.iter().eq(expected.iter()));
+        }
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut deque = wrapped(2, 3);
+        *deque.get_mut(0).unwrap() += 10;
+        *deque.get_mut(4).unwrap() += 10;
+        assert_eq!(deque.get_mut(5), None);
+
+        deque[1] *= 5;
+        deque[2] = 7;
+        assert_eq!(deque[0], 8);
+        assert_eq!(deque[1], -5);
+        assert_eq!(deque[2], 7);
+        assert_eq!(deque[4], 12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let deque = wrapped(2, 3);
+        let _ = deque[5];
+    }
+
+    #[test]
+    fn front_back() {
+        let mut deque = Deque::new();
+        assert_eq!(deque.front(), None);
+        assert_eq!(deque.back(), None);
+        assert_eq!(deque.front_mut(), None);
+        assert_eq!(deque.back_mut(), None);
+
+        deque.push_back(1);
+        assert_eq!(deque.front(), Some(&1));
+        assert_eq!(deque.back(), Some(&1));
+
+        let mut deque = wrapped(2, 3);
+        assert_eq!(deque.front(), Some(&-2));
+        assert_eq!(deque.back(), Some(&2));
+
+        *deque.front_mut().unwrap() = 10;
+        *deque.back_mut().unwrap() = 20;
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(items, [10, -1, 0, 1, 20]);
+    }
+
+    #[test]
+    fn contains() {
+        let deque = wrapped(2, 3);
+        assert!(deque.contains(&-2));
+        assert!(deque.contains(&-1));
+        assert!(deque.contains(&0));
+        assert!(deque.contains(&2));
+        assert!(!deque.contains(&3));
+
+        let deque: Deque<u32> = Deque::new();
+        assert!(!deque.contains(&0));
+    }
+
+    #[test]
+    fn binary_search_by() {
+        // Items are `-4..6`, wrapping between `-1` and `0`
+        let deque = wrapped(4, 6);
+        assert!(!deque.as_slices().1.is_empty());
+
+        for (idx, item) in (-4..6).enumerate() {
+            assert_eq!(deque.binary_search_by(|x| x.cmp(&item)), Ok(idx));
+        }
+        assert_eq!(deque.binary_search_by(|x| x.cmp(&-10)), Err(0));
+        assert_eq!(deque.binary_search_by(|x| x.cmp(&10)), Err(10));
+
+        let mut deque = wrapped(4, 6);
+        deque.remove(4);
+        assert_eq!(deque.binary_search_by(|x| x.cmp(&0)), Err(4));
+        deque.remove(1);
+        assert_eq!(deque.binary_search_by(|x| x.cmp(&-3)), Err(1));
+
+        let deque: Deque<u32> = Deque::new();
+        assert_eq!(deque.binary_search_by(|x| x.cmp(&1)), Err(0));
+    }
+
+    #[test]
+    fn from_vec() {
+        let mut vec = Vec::with_capacity(16);
+        vec.extend((0..10).map(|i| i.to_string()));
+        let ptr = vec.as_ptr();
+
+        let mut deque = Deque::from(vec);
+        assert_eq!(deque.buffer.as_ptr() as *const String, ptr);
+        assert_eq!(deque.capacity(), 16);
+        assert_eq!(deque.len(), 10);
+
+        deque.push_front("-1".to_owned());
+        let items: Vec<_> = deque.into_iter().collect();
+        assert_eq!(
+            items,
+            ["-1", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9"]
+        );
+
+        // Grown to the power of two
+        let deque = Deque::from(vec![1, 2, 3, 4, 5]);
+        assert_eq!(deque.capacity(), 8);
+        assert_eq!(deque.as_slices(), (&[1, 2, 3, 4, 5][..], &[][..]));
+
+        let deque: Deque<u32> = Deque::from(vec![]);
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn clone_debug() {
+        let deque = wrapped(2, 3);
+        let cloned = deque.clone();
+        assert_eq!(deque, cloned);
+        assert_eq!(format!("{cloned:?}"), "[-2, -1, 0, 1, 2]");
+
+        let deque: Deque<String> = Deque::new();
+        assert_eq!(format!("{:?}", deque.clone()), "[]");
+    }
 }
@@ -0,0 +1,290 @@
+use std::marker::PhantomData;
+
+use crate::Deque;
+
+/// Order kept by the `MonotonicDeque`
+pub trait Order {
+    /// Returns `true` if the `older` item can never be the extreme again once the `newer` one is
+    /// pushed
+    fn dominates<T: Ord>(newer: &T, older: &T) -> bool;
+}
+
+/// Keeps track of the maximum
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Max;
+
+impl Order for Max {
+    fn dominates<T: Ord>(newer: &T, older: &T) -> bool {
+        newer >= older
+    }
+}
+
+/// Keeps track of the minimum
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Min;
+
+impl Order for Min {
+    fn dominates<T: Ord>(newer: &T, older: &T) -> bool {
+        newer <= older
+    }
+}
+
+/// Deque of the pushed items, keeping only those which can still become the extreme (maximum or
+/// minimum, depending on `O`) of the sliding window.
+///
+/// Every pushed item gets the consecutive index, and the items older than the given index can be
+/// expired. The items are kept sorted by the order, so the extreme is always at the front. Each
+/// item is pushed and popped at most once, so all the operations are amortized O(1).
+pub struct MonotonicDeque<T, O = Max> {
+    items: Deque<(usize, T)>,
+    next: usize,
+    _order: PhantomData<O>,
+}
+
+impl<T: Ord, O: Order> MonotonicDeque<T, O> {
+    pub fn new() -> Self {
+        Self {
+            items: Deque::new(),
+            next: 0,
+            _order: PhantomData,
+        }
+    }
+
+    /// Number of the kept items
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Pushes the item, returning its index
+    pub fn push(&mut self, item: T) -> usize {
+        while let Some((_, back)) = self.items.back() {
+            if !O::dominates(&item, back) {
+                break;
+            }
+            self.items.pop_back();
+        }
+
+        let index = self.next;
+        self.items.push_back((index, item));
+        self.next += 1;
+        index
+    }
+
+    /// Removes all the items with the index lower than `index`
+    pub fn expire_before(&mut self, index: usize) {
+        while let Some((idx, _)) = self.items.front() {
+            if *idx >= index {
+                break;
+            }
+            self.items.pop_front();
+        }
+    }
+
+    /// Returns the extreme of the not expired items
+    pub fn front(&self) -> Option<&T> {
+        self.items.front().map(|(_, item)| item)
+    }
+
+    /// Returns the extreme of the not expired items, with its index
+    pub fn front_indexed(&self) -> Option<(usize, &T)> {
+        self.items.front().map(|(idx, item)| (*idx, item))
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl<T: Ord> MonotonicDeque<T, Max> {
+    pub fn max(&self) -> Option<&T> {
+        self.front()
+    }
+}
+
+impl<T: Ord> MonotonicDeque<T, Min> {
+    pub fn min(&self) -> Option<&T> {
+        self.front()
+    }
+}
+
+impl<T: Ord, O: Order> Default for MonotonicDeque<T, O> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator over the extremes of all the `size` long windows of the underlying iterator
+pub struct Windows<I: Iterator, O> {
+    iter: I,
+    size: usize,
+    deque: MonotonicDeque<I::Item, O>,
+}
+
+pub type WindowsMax<I> = Windows<I, Max>;
+pub type WindowsMin<I> = Windows<I, Min>;
+
+impl<I, O> Windows<I, O>
+where
+    I: Iterator,
+    I::Item: Ord,
+    O: Order,
+{
+    fn new(iter: I, size: usize) -> Self {
+        if size == 0 {
+            panic!("Window size has to be non-zero");
+        }
+
+        Self {
+            iter,
+            size,
+            deque: MonotonicDeque::new(),
+        }
+    }
+}
+
+impl<I, O> Iterator for Windows<I, O>
+where
+    I: Iterator,
+    I::Item: Ord + Clone,
+    O: Order,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let idx = self.deque.push(self.iter.next()?);
+            self.deque
+                .expire_before((idx + 1).saturating_sub(self.size));
+
+            if idx + 1 >= self.size {
+                return self.deque.front().cloned();
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Items still needed to fill the first window
+        let missing = (self.size - 1).saturating_sub(self.deque.next);
+        let (lo, hi) = self.iter.size_hint();
+        (
+            lo.saturating_sub(missing),
+            hi.map(|hi| hi.saturating_sub(missing)),
+        )
+    }
+}
+
+/// Adds sliding window adapters to all the iterators
+pub trait WindowsExt: Iterator + Sized {
+    /// Iterates over maximums of all the `size` long windows
+    fn windows_max(self, size: usize) -> WindowsMax<Self>
+    where
+        Self::Item: Ord,
+    {
+        Windows::new(self, size)
+    }
+
+    /// Iterates over minimums of all the `size` long windows
+    fn windows_min(self, size: usize) -> WindowsMin<Self>
+    where
+        Self::Item: Ord,
+    {
+        Windows::new(self, size)
+    }
+}
+
+impl<I: Iterator> WindowsExt for I {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::random;
+
+    /// Pseudo-random data with many duplicates
+    fn data(len: usize) -> Vec<u32> {
+        random(42).map(|x| x as u32 % 50).take(len).collect()
+    }
+
+    #[test]
+    fn max() {
+        let mut deque: MonotonicDeque<u32> = MonotonicDeque::new();
+        assert_eq!(deque.max(), None);
+
+        assert_eq!(deque.push(3), 0);
+        assert_eq!(deque.push(1), 1);
+        assert_eq!(deque.push(2), 2);
+        assert_eq!(deque.max(), Some(&3));
+        // `1` can never be the maximum anymore
+        assert_eq!(deque.len(), 2);
+
+        deque.expire_before(1);
+        assert_eq!(deque.front_indexed(), Some((2, &2)));
+
+        deque.push(2);
+        assert_eq!(deque.len(), 1);
+        assert_eq!(deque.front_indexed(), Some((3, &2)));
+
+        deque.expire_before(10);
+        assert!(deque.is_empty());
+        assert_eq!(deque.push(0), 4);
+    }
+
+    #[test]
+    fn min() {
+        let mut deque: MonotonicDeque<u32, Min> = MonotonicDeque::new();
+        deque.push(3);
+        deque.push(1);
+        deque.push(2);
+        assert_eq!(deque.min(), Some(&1));
+        assert_eq!(deque.len(), 2);
+
+        deque.expire_before(2);
+        assert_eq!(deque.min(), Some(&2));
+
+        deque.clear();
+        assert_eq!(deque.min(), None);
+    }
+
+    #[test]
+    fn windows() {
+        let data = data(500);
+
+        for size in [1, 2, 3, 7, 50, 499, 500] {
+            let max: Vec<_> = data.iter().copied().windows_max(size).collect();
+            let expected: Vec<_> = data
+                .windows(size)
+                .map(|window| *window.iter().max().unwrap())
+                .collect();
+            assert_eq!(max, expected);
+
+            let min: Vec<_> = data.iter().windows_min(size).copied().collect();
+            let expected: Vec<_> = data
+                .windows(size)
+                .map(|window| *window.iter().min().unwrap())
+                .collect();
+            assert_eq!(min, expected);
+        }
+
+        assert_eq!(data.iter().windows_max(501).next(), None);
+    }
+
+    #[test]
+    fn size_hint() {
+        let mut windows = (0..10).windows_max(4);
+        assert_eq!(windows.size_hint(), (7, Some(7)));
+        windows.next();
+        assert_eq!(windows.size_hint(), (6, Some(6)));
+        assert_eq!(windows.count(), 6);
+
+        assert_eq!((0..3).windows_max(4).size_hint(), (0, Some(0)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_window() {
+        (0..10).windows_max(0);
+    }
+}
@@ -0,0 +1,546 @@
+//! Lock-free single-producer single-consumer queue.
+//!
+//! It uses the same power-of-two masked buffer as the `Deque`, but instead of `head` and `len`
+//! it keeps two free-running counters: `head` is only advanced by the consumer, and `tail` only
+//! by the producer. The counters wrap around `usize`, which is a multiple of the buffer length, so
+//! masking them always gives the proper buffer index, and `tail - head` is the number of items.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{fence, AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+
+use crate::Deque;
+
+/// Creates a queue with space for `capacity` items, returning its two ends
+pub fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    if capacity == 0 {
+        panic!("Capacity has to be non-zero");
+    }
+
+    let len = Deque::<T>::make_shift(0, capacity);
+    let buffer = std::iter::repeat_with(|| UnsafeCell::new(MaybeUninit::uninit()))
+        .take(len)
+        .collect();
+
+    let shared = Arc::new(Shared {
+        buffer,
+        capacity,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        disconnected: AtomicBool::new(false),
+        producer: Waiter::new(),
+        consumer: Waiter::new(),
+    });
+
+    (
+        Producer {
+            shared: shared.clone(),
+        },
+        Consumer { shared },
+    )
+}
+
+/// Safety:
+/// * `buffer.len()` is a power of two, and `tail - head <= capacity <= buffer.len()`
+/// * Items at `head..tail` (masked) are initialized, and all the others are not
+/// * Items at `head..tail` are only accessed by the consumer, and the others by the producer. The
+///   `tail` is released by the producer after writing, and `head` by the consumer after reading,
+///   so the other side sees the items in the proper state.
+struct Shared<T> {
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    capacity: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    disconnected: AtomicBool,
+    producer: Waiter,
+    consumer: Waiter,
+}
+
+impl<T> Shared<T> {
+    /// Pointer to the slot for the counter `idx`
+    fn slot(&self, idx: usize) -> *mut T {
+        let buffer = UnsafeCell::raw_get(self.buffer.as_ptr()) as *mut T;
+        // Safety:
+        // Masked index is always within the buffer
+        unsafe { buffer.add(idx & (self.buffer.len() - 1)) }
+    }
+
+    /// Number of items, approximated when the other side is changing it concurrently
+    fn len(&self) -> usize {
+        // Loading `head` first, so it is never ahead of `tail`
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head).min(self.capacity)
+    }
+
+    /// Splits `count` items starting at the counter `start` into the ranges of buffer indices
+    /// before and after the wrap point
+    fn ranges(
+        &self,
+        start: usize,
+        count: usize,
+    ) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        let start = start & (self.buffer.len() - 1);
+        let first = count.min(self.buffer.len() - start);
+        (start..start + first, 0..count - first)
+    }
+
+    fn disconnect(&self) {
+        self.disconnected.store(true, Ordering::Release);
+        self.producer.notify();
+        self.consumer.notify();
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+
+        for idx in 0..tail.wrapping_sub(head) {
+            unsafe { self.slot(head.wrapping_add(idx)).drop_in_place() };
+        }
+    }
+}
+
+/// Parks the thread waiting for the other side of the queue.
+///
+/// The waiting thread raises the `parked` flag before checking the condition for the last time,
+/// and the notifying thread checks the flag after changing the condition. Both sides separate it
+/// with the `SeqCst` fence, so either the waiter sees the change, or the notifier sees the flag -
+/// the wakeup is never lost.
+struct Waiter {
+    parked: AtomicBool,
+    thread: Mutex<Option<Thread>>,
+}
+
+impl Waiter {
+    fn new() -> Self {
+        Self {
+            parked: AtomicBool::new(false),
+            thread: Mutex::new(None),
+        }
+    }
+
+    fn wait_until(&self, mut cond: impl FnMut() -> bool) {
+        while !cond() {
+            *self.thread.lock().unwrap() = Some(thread::current());
+            self.parked.store(true, Ordering::SeqCst);
+            fence(Ordering::SeqCst);
+
+            if cond() {
+                self.parked.store(false, Ordering::Relaxed);
+                return;
+            }
+
+            // Spurious wakeups are handled by checking the condition again
+            thread::park();
+        }
+    }
+
+    fn notify(&self) {
+        fence(Ordering::SeqCst);
+        if self.parked.swap(false, Ordering::SeqCst) {
+            if let Some(thread) = &*self.thread.lock().unwrap() {
+                thread.unpark();
+            }
+        }
+    }
+}
+
+/// Pushing end of the queue.
+///
+/// It can be sent to the other thread, but not shared between threads, so there is always a
+/// single producer.
+pub struct Producer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+unsafe impl<T: Send> Send for Producer<T> {}
+
+impl<T> Producer<T> {
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.shared.capacity
+    }
+
+    /// Returns `true` if the `Consumer` is dropped
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Number of items which can be pushed, and the counter of the first free slot
+    fn free(&self) -> (usize, usize) {
+        let tail = self.shared.tail.load(Ordering::Relaxed);
+        let head = self.shared.head.load(Ordering::Acquire);
+        (self.shared.capacity - tail.wrapping_sub(head), tail)
+    }
+
+    /// Publishes `count` written items to the consumer
+    fn commit(&self, tail: usize, count: usize) {
+        self.shared
+            .tail
+            .store(tail.wrapping_add(count), Ordering::Release);
+        self.shared.consumer.notify();
+    }
+
+    /// Pushes the item, returning it back if the queue is full
+    pub fn push(&self, item: T) -> Result<(), T> {
+        let (free, tail) = self.free();
+        if free == 0 {
+            return Err(item);
+        }
+
+        // Safety:
+        // The slot at `tail` is free, so the consumer does not access it
+        unsafe { self.shared.slot(tail).write(item) };
+        self.commit(tail, 1);
+        Ok(())
+    }
+
+    /// Pushes the item, parking the thread while the queue is full. Returns the item back if the
+    /// `Consumer` is dropped.
+    pub fn push_blocking(&self, item: T) -> Result<(), T> {
+        self.shared
+            .producer
+            .wait_until(|| !self.is_full() || self.is_disconnected());
+
+        if self.is_disconnected() {
+            return Err(item);
+        }
+
+        self.push(item)
+    }
+
+    /// Pushes as many items from the beginning of `items` as fit, returning their number
+    pub fn push_slice(&self, items: &[T]) -> usize
+    where
+        T: Copy,
+    {
+        let (free, tail) = self.free();
+        let count = free.min(items.len());
+        let (fst, snd) = self.shared.ranges(tail, count);
+
+        // Safety:
+        // All the `count` slots starting at `tail` are free
+        unsafe {
+            let (fst_ptr, snd_ptr) = (self.shared.slot(fst.start), self.shared.slot(0));
+            std::ptr::copy_nonoverlapping(items.as_ptr(), fst_ptr, fst.len());
+            std::ptr::copy_nonoverlapping(items[fst.len()..].as_ptr(), snd_ptr, snd.len());
+        }
+
+        self.commit(tail, count);
+        count
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.shared.disconnect();
+    }
+}
+
+/// Popping end of the queue.
+///
+/// It can be sent to the other thread, but not shared between threads, so there is always a
+/// single consumer.
+pub struct Consumer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+unsafe impl<T: Send> Send for Consumer<T> {}
+
+impl<T> Consumer<T> {
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.shared.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len() == self.shared.capacity
+    }
+
+    /// Returns `true` if the `Producer` is dropped
+    pub fn is_disconnected(&self) -> bool {
+        self.shared.disconnected.load(Ordering::Acquire)
+    }
+
+    /// Number of items which can be popped, and the counter of the first one
+    fn available(&self) -> (usize, usize) {
+        let head = self.shared.head.load(Ordering::Relaxed);
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        (tail.wrapping_sub(head), head)
+    }
+
+    /// Releases `count` read slots to the producer
+    fn commit(&self, head: usize, count: usize) {
+        self.shared
+            .head
+            .store(head.wrapping_add(count), Ordering::Release);
+        self.shared.producer.notify();
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let (available, head) = self.available();
+        if available == 0 {
+            return None;
+        }
+
+        // Safety:
+        // The slot at `head` is initialized, and the producer does not access it
+        let item = unsafe { self.shared.slot(head).read() };
+        self.commit(head, 1);
+        Some(item)
+    }
+
+    /// Pops the item, parking the thread while the queue is empty. Returns `None` if the queue is
+    /// empty and the `Producer` is dropped.
+    pub fn pop_blocking(&self) -> Option<T> {
+        self.shared
+            .consumer
+            .wait_until(|| !self.is_empty() || self.is_disconnected());
+
+        self.pop()
+    }
+
+    /// Pops as many items as fit into `buf`, returning their number
+    pub fn pop_slice(&self, buf: &mut [T]) -> usize
+    where
+        T: Copy,
+    {
+        let (available, head) = self.available();
+        let count = available.min(buf.len());
+        let (fst, snd) = self.shared.ranges(head, count);
+
+        // Safety:
+        // All the `count` slots starting at `head` are initialized
+        unsafe {
+            let (fst_ptr, snd_ptr) = (self.shared.slot(fst.start), self.shared.slot(0));
+            std::ptr::copy_nonoverlapping(fst_ptr, buf.as_mut_ptr(), fst.len());
+            std::ptr::copy_nonoverlapping(snd_ptr, buf[fst.len()..].as_mut_ptr(), snd.len());
+        }
+
+        self.commit(head, count);
+        count
+    }
+}
+
+impl<T> Drop for Consumer<T> {
+    fn drop(&mut self) {
+        self.shared.disconnect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::test_utils::miri_or;
+
+    /// Number of items sent in the stress tests
+    const ITEMS: usize = miri_or(200, 200_000);
+
+    #[test]
+    fn push_pop() {
+        let (producer, consumer) = channel(3);
+        assert_eq!(producer.capacity(), 3);
+        assert!(consumer.is_empty());
+
+        for round in 0..10 {
+            assert_eq!(producer.push(round * 3), Ok(()));
+            assert_eq!(producer.push(round * 3 + 1), Ok(()));
+            assert_eq!(producer.push(round * 3 + 2), Ok(()));
+            assert!(producer.is_full());
+            assert_eq!(producer.push(100), Err(100));
+
+            assert_eq!(consumer.len(), 3);
+            assert_eq!(consumer.pop(), Some(round * 3));
+            assert_eq!(consumer.pop(), Some(round * 3 + 1));
+            assert_eq!(consumer.pop(), Some(round * 3 + 2));
+            assert_eq!(consumer.pop(), None);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_capacity() {
+        channel::<u32>(0);
+    }
+
+    #[test]
+    fn slices() {
+        let (producer, consumer) = channel(8);
+        let mut buf = [0; 8];
+
+        // Moving the counters, so the slices wrap around the buffer
+        assert_eq!(producer.push_slice(&[1, 2, 3, 4, 5]), 5);
+        assert_eq!(consumer.pop_slice(&mut buf[..3]), 3);
+        assert_eq!(buf[..3], [1, 2, 3]);
+
+        assert_eq!(producer.push_slice(&[6, 7, 8, 9, 10, 11, 12]), 6);
+        assert!(producer.is_full());
+        assert_eq!(producer.push_slice(&[12]), 0);
+
+        assert_eq!(consumer.pop_slice(&mut buf), 8);
+        assert_eq!(buf, [4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(consumer.pop_slice(&mut buf), 0);
+    }
+
+    #[test]
+    fn drops_items() {
+        let item = Rc::new(());
+        let (producer, consumer) = channel(4);
+        for _ in 0..4 {
+            producer.push(item.clone()).unwrap();
+        }
+        drop(consumer.pop());
+        assert_eq!(Rc::strong_count(&item), 4);
+
+        drop(producer);
+        drop(consumer);
+        assert_eq!(Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn disconnect() {
+        let (producer, consumer) = channel(2);
+        producer.push(1).unwrap();
+        drop(producer);
+
+        assert!(consumer.is_disconnected());
+        assert_eq!(consumer.pop_blocking(), Some(1));
+        assert_eq!(consumer.pop_blocking(), None);
+
+        let (producer, consumer) = channel(1);
+        producer.push(1).unwrap();
+        drop(consumer);
+        assert!(producer.is_disconnected());
+        assert_eq!(producer.push_blocking(2), Err(2));
+    }
+
+    #[test]
+    fn blocking_wakeup() {
+        let (producer, consumer) = channel(1);
+
+        let handle = thread::spawn(move || {
+            let first = consumer.pop_blocking();
+            thread::sleep(Duration::from_millis(10));
+            let rest: Vec<_> = std::iter::from_fn(|| consumer.pop_blocking()).collect();
+            (first, rest)
+        });
+
+        // Let the consumer park on the empty queue
+        thread::sleep(Duration::from_millis(10));
+        producer.push_blocking(1).unwrap();
+        // Parks on the full queue
+        producer.push_blocking(2).unwrap();
+        producer.push_blocking(3).unwrap();
+        drop(producer);
+
+        let (first, rest) = handle.join().unwrap();
+        assert_eq!(first, Some(1));
+        assert_eq!(rest, [2, 3]);
+    }
+
+    #[test]
+    fn stress_blocking() {
+        let (producer, consumer) = channel(16);
+
+        let handle = thread::spawn(move || {
+            for i in 0..ITEMS {
+                producer.push_blocking(i.to_string()).unwrap();
+            }
+        });
+
+        for i in 0..ITEMS {
+            assert_eq!(consumer.pop_blocking(), Some(i.to_string()));
+        }
+        assert_eq!(consumer.pop_blocking(), None);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn stress_spinning() {
+        let (producer, consumer) = channel(7);
+
+        let handle = thread::spawn(move || {
+            let mut next = 0;
+            while next < ITEMS {
+                match producer.push(next) {
+                    Ok(()) => next += 1,
+                    Err(_) => thread::yield_now(),
+                }
+            }
+        });
+
+        let mut next = 0;
+        while next < ITEMS {
+            match consumer.pop() {
+                Some(item) => {
+                    assert_eq!(item, next);
+                    next += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+        handle.join().unwrap();
+        assert!(consumer.is_empty());
+    }
+
+    #[test]
+    fn stress_slices() {
+        let (producer, consumer) = channel(32);
+
+        let handle = thread::spawn(move || {
+            let items: Vec<_> = (0..ITEMS as u64).collect();
+            let mut sent = 0;
+            // Chunks of varying size, so they wrap in different places
+            for size in (1..20).cycle() {
+                if sent == items.len() {
+                    break;
+                }
+
+                let end = (sent + size).min(items.len());
+                sent += producer.push_slice(&items[sent..end]);
+                if producer.is_full() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        let mut received = Vec::with_capacity(ITEMS);
+        let mut buf = [0; 13];
+        while received.len() < ITEMS {
+            let count = consumer.pop_slice(&mut buf);
+            received.extend_from_slice(&buf[..count]);
+            if count == 0 {
+                thread::yield_now();
+            }
+        }
+        handle.join().unwrap();
+
+        assert!(received.iter().copied().eq(0..ITEMS as u64));
+    }
+}
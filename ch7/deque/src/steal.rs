@@ -0,0 +1,453 @@
+//! Chase-Lev work-stealing deque.
+//!
+//! The owning `Worker` pushes and pops items at the bottom end, while any number of `Stealer`s
+//! take them from the top end. Like the `Deque`, it keeps the items in a power-of-two circular
+//! buffer, indexed with masked counters, and doubles it when it gets full.
+//!
+//! Based on "Correct and Efficient Work-Stealing for Weak Memory Models" by Lê, Pop, Cohen and
+//! Zappa Nardelli.
+
+use std::cell::{Cell, UnsafeCell};
+use std::marker::PhantomData;
+use std::sync::atomic::{fence, AtomicIsize, AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::Deque;
+
+mod pool;
+
+pub use pool::{scope, Scope};
+
+/// Circular buffer of the deque.
+///
+/// Items are boxed, so the slots can be accessed atomically - a stealer reads the slot before
+/// claiming the item, while the worker may already be reusing it. Such a stealer always fails to
+/// claim the item, so it never uses the pointer it read.
+struct Buffer<T> {
+    slots: Box<[AtomicPtr<T>]>,
+}
+
+impl<T> Buffer<T> {
+    fn new(len: usize) -> Box<Self> {
+        let slots = std::iter::repeat_with(|| AtomicPtr::new(std::ptr::null_mut()))
+            .take(Deque::<T>::make_shift(0, len))
+            .collect();
+        Box::new(Self { slots })
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Slot for the counter `idx`
+    fn slot(&self, idx: isize) -> &AtomicPtr<T> {
+        &self.slots[idx as usize & (self.slots.len() - 1)]
+    }
+
+    fn write(&self, idx: isize, item: T) {
+        let item = Box::into_raw(Box::new(item));
+        self.slot(idx).store(item, Ordering::Relaxed);
+    }
+
+    /// Safety:
+    /// The slot has to be initialized, and the item claimed by the caller
+    unsafe fn take(&self, idx: isize) -> T {
+        *Box::from_raw(self.slot(idx).load(Ordering::Relaxed))
+    }
+}
+
+/// Safety:
+/// * Slots in `top..bottom` of the current `buffer` are pointing to the boxed items
+/// * `top` only grows - it is advanced by whoever claims the top item with CAS
+/// * `bottom` is only changed by the worker
+/// * Replaced buffers are kept in `retired`, as stealers may still read from them. They are kept
+///   as raw pointers, as converting them back to `Box` would assert the unique access.
+/// * Stealers increment `stealing` before loading the `buffer`, and decrement it after they are
+///   done reading it. All the accesses to `stealing` and `buffer` are `SeqCst`, so once the worker
+///   replaces the buffer and then sees no steal in flight, every later steal sees the new buffer
+///   and the retired ones can be freed.
+struct Inner<T> {
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+    buffer: AtomicPtr<Buffer<T>>,
+    /// Only accessed by the worker, or on drop
+    retired: UnsafeCell<Vec<*mut Buffer<T>>>,
+    /// Number of steals which may be reading the buffer
+    stealing: AtomicUsize,
+}
+
+impl<T> Inner<T> {
+    /// Frees the retired buffers, if no stealer can read them anymore.
+    ///
+    /// Safety:
+    /// It can be called only by the worker
+    unsafe fn reclaim(&self) {
+        let retired = &mut *self.retired.get();
+        if retired.is_empty() || self.stealing.load(Ordering::SeqCst) != 0 {
+            return;
+        }
+
+        // Retired buffers contain only copies of the items, so they are not dropped
+        for buffer in retired.drain(..) {
+            drop(unsafe { Box::from_raw(buffer) });
+        }
+    }
+}
+
+// Safety:
+// Items are only moved between threads, never shared, and the buffers are only freed when no
+// stealer can read them
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let top = *self.top.get_mut();
+        let bottom = *self.bottom.get_mut();
+        let buffer = unsafe { Box::from_raw(*self.buffer.get_mut()) };
+
+        for idx in top..bottom {
+            drop(unsafe { buffer.take(idx) });
+        }
+
+        // Retired buffers contain only copies of the items, so they are not dropped
+        for buffer in self.retired.get_mut().drain(..) {
+            drop(unsafe { Box::from_raw(buffer) });
+        }
+    }
+}
+
+/// Owning end of the deque.
+///
+/// It can be sent to the other thread, but not shared between threads, so there is always a
+/// single worker.
+pub struct Worker<T> {
+    inner: Arc<Inner<T>>,
+    _not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Worker<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let buffer = Box::into_raw(Buffer::new(capacity));
+
+        Self {
+            inner: Arc::new(Inner {
+                top: AtomicIsize::new(0),
+                bottom: AtomicIsize::new(0),
+                buffer: AtomicPtr::new(buffer),
+                retired: UnsafeCell::new(vec![]),
+                stealing: AtomicUsize::new(0),
+            }),
+            _not_sync: PhantomData,
+        }
+    }
+
+    /// Creates a new stealer, taking items from this worker
+    pub fn stealer(&self) -> Stealer<T> {
+        Stealer {
+            inner: self.inner.clone(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed);
+        let top = self.inner.top.load(Ordering::Relaxed);
+        (bottom - top).max(0) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn buffer(&self) -> &Buffer<T> {
+        // Safety:
+        // The buffer is only replaced by the worker, and the old one is kept alive
+        unsafe { &*self.inner.buffer.load(Ordering::Relaxed) }
+    }
+
+    /// Moves the items to the buffer twice as big
+    fn grow(&self, top: isize, bottom: isize) -> &Buffer<T> {
+        let old = self.buffer();
+        let new = Buffer::<T>::new(old.capacity() * 2);
+
+        // Items are copied, not moved, as stealers may still read them from the old buffer, but
+        // only the one claiming the item owns it
+        for idx in top..bottom {
+            let item = old.slot(idx).load(Ordering::Relaxed);
+            new.slot(idx).store(item, Ordering::Relaxed);
+        }
+
+        let new = Box::into_raw(new);
+        let old = self.inner.buffer.swap(new, Ordering::SeqCst);
+        // Safety:
+        // Only the worker accesses the retired buffers
+        unsafe {
+            (*self.inner.retired.get()).push(old);
+            self.inner.reclaim();
+        }
+
+        unsafe { &*new }
+    }
+
+    /// Pushes the item at the bottom
+    pub fn push(&self, item: T) {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed);
+        let top = self.inner.top.load(Ordering::Acquire);
+
+        let mut buffer = self.buffer();
+        if bottom - top >= buffer.capacity() as isize {
+            buffer = self.grow(top, bottom);
+        } else {
+            // Stealers may have been reading the retired buffers while the last one was growing
+            unsafe { self.inner.reclaim() };
+        }
+
+        buffer.write(bottom, item);
+        fence(Ordering::Release);
+        self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+    }
+
+    /// Pops the item from the bottom - the most recently pushed one
+    pub fn pop(&self) -> Option<T> {
+        let bottom = self.inner.bottom.load(Ordering::Relaxed) - 1;
+        let buffer = self.buffer();
+        // Reserving the bottom item before checking if stealers did not take it
+        self.inner.bottom.store(bottom, Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let top = self.inner.top.load(Ordering::Relaxed);
+
+        if top > bottom {
+            // Empty
+            self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+            return None;
+        }
+
+        if top < bottom {
+            // More than one item, so stealers can't reach the reserved one
+            return Some(unsafe { buffer.take(bottom) });
+        }
+
+        // The last item - racing with stealers for it
+        let won = self
+            .inner
+            .top
+            .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+            .is_ok();
+        self.inner.bottom.store(bottom + 1, Ordering::Relaxed);
+
+        won.then(|| unsafe { buffer.take(bottom) })
+    }
+}
+
+impl<T> Default for Worker<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Result of the stealing attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Steal<T> {
+    Empty,
+    Success(T),
+    /// Lost the race for the item with another thread - it is worth trying again
+    Retry,
+}
+
+/// Stealing end of the deque. It can be cloned and shared between threads.
+pub struct Stealer<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Stealer<T> {
+    pub fn is_empty(&self) -> bool {
+        let top = self.inner.top.load(Ordering::Acquire);
+        let bottom = self.inner.bottom.load(Ordering::Acquire);
+        bottom <= top
+    }
+
+    /// Steals the item from the top - the least recently pushed one
+    pub fn steal(&self) -> Steal<T> {
+        let top = self.inner.top.load(Ordering::Acquire);
+        fence(Ordering::SeqCst);
+        let bottom = self.inner.bottom.load(Ordering::Acquire);
+
+        if top >= bottom {
+            return Steal::Empty;
+        }
+
+        // Safety:
+        // While `stealing` is incremented, the loaded buffer is not freed. The item is read before
+        // claiming it, as after the CAS the worker may overwrite its slot.
+        self.inner.stealing.fetch_add(1, Ordering::SeqCst);
+        let buffer = unsafe { &*self.inner.buffer.load(Ordering::SeqCst) };
+        let item = buffer.slot(top).load(Ordering::Relaxed);
+        self.inner.stealing.fetch_sub(1, Ordering::Release);
+
+        match self
+            .inner
+            .top
+            .compare_exchange(top, top + 1, Ordering::SeqCst, Ordering::Relaxed)
+        {
+            Ok(_) => Steal::Success(*unsafe { Box::from_raw(item) }),
+            Err(_) => Steal::Retry,
+        }
+    }
+
+    /// Steals the item, retrying until it succeeds or the deque is empty
+    pub fn steal_blocking(&self) -> Option<T> {
+        loop {
+            match self.steal() {
+                Steal::Empty => return None,
+                Steal::Success(item) => return Some(item),
+                Steal::Retry => std::hint::spin_loop(),
+            }
+        }
+    }
+}
+
+impl<T> Clone for Stealer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    use super::*;
+    use crate::test_utils::miri_or;
+
+    const ITEMS: usize = miri_or(200, 100_000);
+
+    #[test]
+    fn worker() {
+        let worker = Worker::new();
+        assert!(worker.is_empty());
+        assert_eq!(worker.pop(), None);
+
+        // Growing from the initial 8 items
+        for i in 0..20 {
+            worker.push(i.to_string());
+        }
+        assert_eq!(worker.len(), 20);
+
+        for i in (0..20).rev() {
+            assert_eq!(worker.pop(), Some(i.to_string()));
+        }
+        assert_eq!(worker.pop(), None);
+        assert!(worker.is_empty());
+    }
+
+    #[test]
+    fn steal() {
+        let worker = Worker::with_capacity(4);
+        let stealer = worker.stealer();
+        assert_eq!(stealer.steal(), Steal::Empty);
+
+        for i in 0..10 {
+            worker.push(i);
+        }
+        assert_eq!(stealer.steal(), Steal::Success(0));
+        assert_eq!(stealer.clone().steal(), Steal::Success(1));
+        assert_eq!(worker.pop(), Some(9));
+
+        // Wrapping around the buffer
+        for i in 10..15 {
+            worker.push(i);
+        }
+        let stolen: Vec<_> = std::iter::from_fn(|| stealer.steal_blocking()).collect();
+        assert_eq!(stolen, [2, 3, 4, 5, 6, 7, 8, 10, 11, 12, 13, 14]);
+        assert!(stealer.is_empty());
+        assert_eq!(worker.pop(), None);
+    }
+
+    #[test]
+    fn drops_items() {
+        let item = std::rc::Rc::new(());
+        let worker = Worker::new();
+        for _ in 0..20 {
+            worker.push(item.clone());
+        }
+        drop(worker.pop());
+        drop(worker.stealer().steal());
+        assert_eq!(std::rc::Rc::strong_count(&item), 19);
+
+        drop(worker);
+        assert_eq!(std::rc::Rc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn reclaims_buffers() {
+        let worker = Worker::new();
+        let stealer = worker.stealer();
+        let retired = || unsafe { (*worker.inner.retired.get()).len() };
+
+        for i in 0..100 {
+            worker.push(i);
+            assert_eq!(retired(), 0);
+        }
+
+        // Steal in flight while growing - the old buffer is freed on the next push
+        worker.inner.stealing.fetch_add(1, Ordering::SeqCst);
+        for i in 100..200 {
+            worker.push(i);
+        }
+        assert_eq!(retired(), 1);
+        worker.inner.stealing.fetch_sub(1, Ordering::SeqCst);
+
+        worker.push(200);
+        assert_eq!(retired(), 0);
+        assert_eq!(stealer.steal(), Steal::Success(0));
+        assert_eq!(worker.len(), 200);
+    }
+
+    #[test]
+    fn stress() {
+        // Every item is taken exactly once - either by the worker or one of the stealers
+        let worker = Worker::<usize>::new();
+        let taken: Vec<_> = (0..ITEMS).map(|_| AtomicUsize::new(0)).collect();
+        let taken = &taken;
+        let done = std::sync::atomic::AtomicBool::new(false);
+
+        thread::scope(|s| {
+            for _ in 0..3 {
+                let stealer = worker.stealer();
+                let done = &done;
+                s.spawn(move || loop {
+                    match stealer.steal() {
+                        Steal::Success(item) => {
+                            taken[item].fetch_add(1, Ordering::Relaxed);
+                        }
+                        Steal::Empty if done.load(Ordering::Acquire) => break,
+                        _ => thread::yield_now(),
+                    }
+                });
+            }
+
+            for i in 0..ITEMS {
+                worker.push(i);
+                // Popping sometimes, so the worker races with stealers for the last items
+                if i % 3 == 0 {
+                    if let Some(item) = worker.pop() {
+                        taken[item].fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            while let Some(item) = worker.pop() {
+                taken[item].fetch_add(1, Ordering::Relaxed);
+            }
+            done.store(true, Ordering::Release);
+        });
+
+        assert!(taken.iter().all(|count| count.load(Ordering::Relaxed) == 1));
+    }
+}
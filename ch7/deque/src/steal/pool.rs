@@ -0,0 +1,404 @@
+use std::any::Any;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread;
+
+use super::{Steal, Stealer, Worker};
+
+type Task<'env> = Box<dyn FnOnce(&Scope<'_, 'env>) + Send + 'env>;
+
+/// State shared by all the threads of the pool
+struct Pool<'env> {
+    stealers: Vec<Stealer<Task<'env>>>,
+    /// Number of spawned tasks, which are not finished yet
+    pending: AtomicUsize,
+    /// Set when the scope is finished, so the threads can exit
+    done: AtomicBool,
+    /// Payload of the first panicking spawned task
+    panic: Mutex<Option<Box<dyn Any + Send>>>,
+    /// Number of the threads going to sleep, or sleeping
+    sleeping: AtomicUsize,
+    /// Held by the sleeping thread while it checks there is nothing to do, so no wake up is lost
+    sleep: Mutex<()>,
+    wake: Condvar,
+}
+
+impl Pool<'_> {
+    /// Wakes up the sleeping threads, so they check for the new tasks or a finished condition
+    fn notify(&self) {
+        // Pairs with the fence in `Scope::sleep` - either the sleeping thread sees the change, or
+        // this sees the thread sleeping
+        atomic::fence(Ordering::SeqCst);
+        if self.sleeping.load(Ordering::Relaxed) > 0 {
+            drop(self.sleep.lock().unwrap_or_else(PoisonError::into_inner));
+            self.wake.notify_all();
+        }
+    }
+}
+
+/// Result of the second closure of `join`, shared by the task and the joining thread.
+///
+/// It is owned by both of them, so the thief can still store the flag after the joining thread
+/// has already returned.
+struct JoinLatch<R> {
+    finished: AtomicBool,
+    result: Mutex<Option<thread::Result<R>>>,
+}
+
+/// Waits for the `join` task when dropped - even if the joining thread is unwinding
+struct JoinGuard<'a, 'pool, 'env, R> {
+    scope: &'a Scope<'pool, 'env>,
+    latch: &'a JoinLatch<R>,
+}
+
+impl<R> Drop for JoinGuard<'_, '_, '_, R> {
+    fn drop(&mut self) {
+        let finished = || self.latch.finished.load(Ordering::Acquire);
+        while catch_unwind(AssertUnwindSafe(|| self.scope.run_until(finished))).is_err() {}
+    }
+}
+
+/// Handle for spawning tasks on the pool, given to every task.
+///
+/// Tasks are pushed to the deque of the current thread, and idle threads steal them.
+pub struct Scope<'pool, 'env> {
+    index: usize,
+    worker: &'pool Worker<Task<'env>>,
+    pool: &'pool Pool<'env>,
+}
+
+/// Runs `f` on the pool of `threads` threads (including the current one), waiting for all the
+/// tasks it spawns.
+///
+/// If any of the spawned tasks panics, the panic is propagated after all the tasks finish.
+pub fn scope<'env, R>(threads: usize, f: impl FnOnce(&Scope<'_, 'env>) -> R) -> R {
+    let threads = threads.max(1);
+    let workers: Vec<Worker<Task<'env>>> = (0..threads).map(|_| Worker::new()).collect();
+    let pool = Pool {
+        stealers: workers.iter().map(Worker::stealer).collect(),
+        pending: AtomicUsize::new(0),
+        done: AtomicBool::new(false),
+        panic: Mutex::new(None),
+        sleeping: AtomicUsize::new(0),
+        sleep: Mutex::new(()),
+        wake: Condvar::new(),
+    };
+    let pool = &pool;
+
+    let mut workers = workers.into_iter().enumerate();
+    let (index, worker) = workers.next().unwrap();
+
+    let result = thread::scope(|s| {
+        for (index, worker) in workers {
+            s.spawn(move || {
+                let scope = Scope {
+                    index,
+                    worker: &worker,
+                    pool,
+                };
+                scope.run_until(|| pool.done.load(Ordering::Acquire));
+            });
+        }
+
+        let scope = Scope {
+            index,
+            worker: &worker,
+            pool,
+        };
+        // Even when `f` panics, the spawned tasks have to finish before the threads are joined
+        let result = catch_unwind(AssertUnwindSafe(|| f(&scope)));
+        scope.run_until(|| pool.pending.load(Ordering::Acquire) == 0);
+        pool.done.store(true, Ordering::Release);
+        pool.notify();
+        result
+    });
+
+    if let Some(payload) = pool.panic.lock().unwrap().take() {
+        resume_unwind(payload);
+    }
+
+    result.unwrap_or_else(|payload| resume_unwind(payload))
+}
+
+impl<'env> Scope<'_, 'env> {
+    /// Number of threads in the pool
+    pub fn threads(&self) -> usize {
+        self.pool.stealers.len()
+    }
+
+    /// Spawns the task to be run by any of the threads
+    pub fn spawn(&self, f: impl FnOnce(&Scope<'_, 'env>) + Send + 'env) {
+        self.pool.pending.fetch_add(1, Ordering::Relaxed);
+        self.worker.push(Box::new(f));
+        self.pool.notify();
+    }
+
+    /// Runs `a` and `b` potentially in parallel, returning both results.
+    ///
+    /// The `b` is pushed to the deque of the current thread, so other threads can steal it while
+    /// `a` is running. Then, until `b` finishes, the thread runs other tasks. If any of the
+    /// closures panics, the panic is propagated after both are finished.
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce(&Scope<'_, 'env>) -> RA,
+        B: FnOnce(&Scope<'_, 'env>) -> RB + Send,
+        RB: Send,
+    {
+        let latch = Arc::new(JoinLatch {
+            finished: AtomicBool::new(false),
+            result: Mutex::new(None),
+        });
+
+        let task = {
+            let latch = latch.clone();
+            move |scope: &Scope<'_, 'env>| {
+                let result = catch_unwind(AssertUnwindSafe(|| b(scope)));
+                *latch.result.lock().unwrap_or_else(PoisonError::into_inner) = Some(result);
+                latch.finished.store(true, Ordering::Release);
+                scope.pool.notify();
+            }
+        };
+        let task: Box<dyn FnOnce(&Scope<'_, 'env>) + Send + '_> = Box::new(task);
+        // Safety:
+        // The `b` borrows from the caller, but this frame is never left before `b` finishes - the
+        // guard keeps waiting even on panic. Anything touched after that is owned by the task.
+        let task: Task<'env> = unsafe { std::mem::transmute(task) };
+
+        self.pool.pending.fetch_add(1, Ordering::Relaxed);
+        self.worker.push(task);
+        self.pool.notify();
+
+        let guard = JoinGuard {
+            scope: self,
+            latch: &latch,
+        };
+        let result_a = catch_unwind(AssertUnwindSafe(|| a(self)));
+        drop(guard);
+
+        let result_b = latch
+            .result
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .unwrap();
+        match (result_a, result_b) {
+            (Ok(a), Ok(b)) => (a, b),
+            (Err(payload), _) | (_, Err(payload)) => resume_unwind(payload),
+        }
+    }
+
+    /// Takes a task from the own deque, or steals it from other threads
+    fn find_task(&self) -> Option<Task<'env>> {
+        if let Some(task) = self.worker.pop() {
+            return Some(task);
+        }
+
+        let stealers = &self.pool.stealers;
+        loop {
+            let mut retry = false;
+            // Starting from the next thread, so the threads don't all steal from the same one
+            for offset in 1..stealers.len() {
+                match stealers[(self.index + offset) % stealers.len()].steal() {
+                    Steal::Success(task) => return Some(task),
+                    Steal::Retry => retry = true,
+                    Steal::Empty => (),
+                }
+            }
+
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    /// Runs the tasks until the `cond` is met, going to sleep when there is nothing to do.
+    ///
+    /// It never panics by itself, as `join` relies on it to wait for its task.
+    fn run_until(&self, cond: impl Fn() -> bool) {
+        let mut idle = 0;
+
+        while !cond() {
+            let task = match self.find_task() {
+                Some(task) => Some(task),
+                None if idle < 64 => {
+                    idle += 1;
+                    thread::yield_now();
+                    None
+                }
+                None => self.sleep(&cond),
+            };
+
+            if let Some(task) = task {
+                idle = 0;
+                if let Err(payload) = catch_unwind(AssertUnwindSafe(|| task(self))) {
+                    let mut panic = self
+                        .pool
+                        .panic
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner);
+                    panic.get_or_insert(payload);
+                }
+                if self.pool.pending.fetch_sub(1, Ordering::Release) == 1 {
+                    self.pool.notify();
+                }
+            }
+        }
+    }
+
+    /// Blocks until woken up by `Pool::notify`, unless the `cond` is already met or there is a
+    /// task to run
+    fn sleep(&self, cond: &impl Fn() -> bool) -> Option<Task<'env>> {
+        let pool = self.pool;
+        pool.sleeping.fetch_add(1, Ordering::Relaxed);
+        atomic::fence(Ordering::SeqCst);
+
+        let guard = pool.sleep.lock().unwrap_or_else(PoisonError::into_inner);
+        let task = if cond() { None } else { self.find_task() };
+        if task.is_none() && !cond() {
+            drop(
+                pool.wake
+                    .wait(guard)
+                    .unwrap_or_else(PoisonError::into_inner),
+            );
+        }
+
+        pool.sleeping.fetch_sub(1, Ordering::Relaxed);
+        task
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{miri_or, random};
+    use std::time::Duration;
+
+    /// Sorts the slice, sorting its halves in parallel
+    fn merge_sort<T: Ord + Copy + Send>(scope: &Scope<'_, '_>, items: &mut [T]) {
+        if items.len() <= 16 {
+            items.sort();
+            return;
+        }
+
+        let (left, right) = items.split_at_mut(items.len() / 2);
+        scope.join(|s| merge_sort(s, left), |s| merge_sort(s, right));
+
+        let mut merged = Vec::with_capacity(items.len());
+        let (mut left, mut right) = items.split_at(items.len() / 2);
+        while let (Some(l), Some(r)) = (left.first(), right.first()) {
+            if r < l {
+                merged.push(*r);
+                right = &right[1..];
+            } else {
+                merged.push(*l);
+                left = &left[1..];
+            }
+        }
+        merged.extend_from_slice(left);
+        merged.extend_from_slice(right);
+
+        items.copy_from_slice(&merged);
+    }
+
+    #[test]
+    fn sort() {
+        let mut items: Vec<u64> = random(42).take(miri_or(200, 100_000)).collect();
+
+        let mut expected = items.clone();
+        expected.sort();
+
+        scope(4, |s| merge_sort(s, &mut items));
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn spawn() {
+        let counter = AtomicUsize::new(0);
+        let counter = &counter;
+
+        let result = scope(3, |s| {
+            assert_eq!(s.threads(), 3);
+            for _ in 0..10 {
+                s.spawn(move |s| {
+                    for _ in 0..10 {
+                        s.spawn(move |_| {
+                            counter.fetch_add(1, Ordering::Relaxed);
+                        });
+                    }
+                });
+            }
+            "done"
+        });
+
+        assert_eq!(result, "done");
+        assert_eq!(counter.load(Ordering::Relaxed), 100);
+    }
+
+    #[test]
+    fn join() {
+        fn fib(scope: &Scope<'_, '_>, n: u64) -> u64 {
+            if n < 2 {
+                return n;
+            }
+            let (a, b) = scope.join(|s| fib(s, n - 1), |s| fib(s, n - 2));
+            a + b
+        }
+
+        let n = if cfg!(miri) { 8 } else { 20 };
+        assert_eq!(scope(4, |s| fib(s, n)), if cfg!(miri) { 21 } else { 6765 });
+        assert_eq!(scope(1, |s| fib(s, n)), if cfg!(miri) { 21 } else { 6765 });
+    }
+
+    #[test]
+    fn wakes_sleeping() {
+        let counter = AtomicUsize::new(0);
+        let counter = &counter;
+
+        scope(3, |s| {
+            // The other threads run out of work and go to sleep meanwhile
+            thread::sleep(Duration::from_millis(20));
+            s.spawn(move |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+
+            s.join(
+                |_| thread::sleep(Duration::from_millis(20)),
+                |_| counter.fetch_add(1, Ordering::Relaxed),
+            );
+        });
+
+        assert_eq!(counter.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn panics() {
+        let finished = AtomicUsize::new(0);
+        let finished = &finished;
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            scope(2, |s| {
+                s.spawn(|_| panic!("Boom"));
+                for _ in 0..10 {
+                    s.spawn(move |_| {
+                        finished.fetch_add(1, Ordering::Relaxed);
+                    });
+                }
+            })
+        }));
+        assert!(res.is_err());
+        assert_eq!(finished.load(Ordering::Relaxed), 10);
+
+        let res = catch_unwind(AssertUnwindSafe(|| {
+            scope(2, |s| {
+                s.join(
+                    |_| (),
+                    |_| {
+                        panic!("Boom");
+                    },
+                )
+            })
+        }));
+        assert!(res.is_err());
+    }
+}